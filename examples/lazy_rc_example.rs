@@ -14,7 +14,7 @@ use rand::random;
 use lazy_rc::LazyRc;
 
 thread_local! {
-    static INSTANCE: LazyRc<MyStruct> = LazyRc::empty();
+    static INSTANCE: LazyRc<MyStruct> = const { LazyRc::empty() };
 }
 
 #[derive(Debug)]
@@ -0,0 +1,221 @@
+/*
+ * lazy_rc - Rc<T> and Arc<T> with *lazy* initialization
+ * This is free and unencumbered software released into the public domain.
+ */
+use std::fmt::Debug;
+use std::sync::{Mutex, MutexGuard, OnceLock};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+
+type FnEstimate<T> = dyn Fn(&T) -> usize + Send + Sync;
+
+/// A memory budget that is shared by many [`Lazy<T, P, L>`](crate::Lazy)
+/// instances, e.g. re-initializable caches
+///
+/// Instances are registered via
+/// [`Lazy::register_budget()`](crate::Lazy::register_budget), along with a
+/// function that *estimates* the size of the "inner" value. Whenever the
+/// total estimated usage exceeds the limit, the *least recently used*
+/// instances are reset, until the usage is within the limit again. Only
+/// instances that have a *default* initializer are reset, so that they can
+/// be rebuilt on the next access. Instances that are locked at that moment,
+/// e.g. because they are being initialized, are skipped.
+///
+/// ```
+/// use lazy_rc::{LazyArc, MemoryBudget};
+/// use std::sync::LazyLock;
+///
+/// static BUDGET: MemoryBudget = MemoryBudget::new(1024);
+/// static CACHE: LazyLock<LazyArc<Vec<u8>>> = LazyLock::new(|| {
+///     LazyArc::with_default_init(|| vec![0u8; 768])
+/// });
+///
+/// CACHE.register_budget(&BUDGET, Vec::len);
+/// CACHE.or_init();
+/// assert_eq!(BUDGET.usage(), 768);
+/// assert_eq!(CACHE.budget_usage(), Some(768));
+/// ```
+///
+/// This type is only available with the `memory-budget` feature enabled.
+pub struct MemoryBudget {
+    limit: AtomicUsize,
+    clock: AtomicU64,
+    enforcing: AtomicBool,
+    pending: AtomicBool,
+    entries: Mutex<Vec<&'static dyn Budgeted>>,
+}
+
+/// The usage of a single registered instance, see [`MemoryBudget::entries()`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BudgetEntry {
+    /// The type name of the "inner" value.
+    pub type_name: &'static str,
+    /// The estimated size of the "inner" value, or zero, if the "inner" value
+    /// is **not** initialized.
+    pub usage: usize,
+    /// Indicates whether the instance can be reset in order to stay within
+    /// the budget, i.e. whether it has a *default* initializer.
+    pub evictable: bool,
+}
+
+/// An instance that can be registered with a [`MemoryBudget`].
+pub(crate) trait Budgeted: Sync {
+    /// Returns the size and the time of the most recent access.
+    fn usage(&self) -> (usize, u64);
+
+    /// Returns the type name of the "inner" value.
+    fn type_name(&self) -> &'static str;
+
+    /// Returns `true`, if the "inner" value can be rebuilt after a reset.
+    fn is_evictable(&self) -> bool;
+
+    /// Resets the "inner" value, if that is possible without blocking.
+    fn try_evict(&self) -> bool;
+}
+
+/// The link of a [`Lazy<T, P, L>`](crate::Lazy) to its [`MemoryBudget`].
+pub(crate) struct BudgetLink<T: ?Sized> {
+    link: OnceLock<(&'static MemoryBudget, Box<FnEstimate<T>>)>,
+    size: AtomicUsize,
+    last_access: AtomicU64,
+}
+
+impl MemoryBudget {
+    /// Create a new memory budget that allows a total estimated usage of
+    /// `limit`, in whatever unit the size estimators return.
+    pub const fn new(limit: usize) -> Self {
+        Self {
+            limit: AtomicUsize::new(limit),
+            clock: AtomicU64::new(1),
+            enforcing: AtomicBool::new(false),
+            pending: AtomicBool::new(false),
+            entries: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Returns the limit of this budget.
+    pub fn limit(&self) -> usize {
+        self.limit.load(Ordering::Relaxed)
+    }
+
+    /// Changes the limit of this budget, resetting the *least recently used*
+    /// instances right away, if the usage now exceeds the limit.
+    pub fn set_limit(&self, limit: usize) {
+        self.limit.store(limit, Ordering::Relaxed);
+        self.enforce();
+    }
+
+    /// Returns the total estimated usage of all registered instances.
+    pub fn usage(&self) -> usize {
+        self.lock().iter().map(|entry| entry.usage().0).sum()
+    }
+
+    /// Returns the estimated usage of each registered instance, in the order
+    /// of registration.
+    pub fn entries(&self) -> Vec<BudgetEntry> {
+        self.lock().iter()
+            .map(|entry| BudgetEntry { type_name: entry.type_name(), usage: entry.usage().0, evictable: entry.is_evictable() })
+            .collect()
+    }
+
+    pub(crate) fn register(&self, instance: &'static dyn Budgeted) {
+        self.lock().push(instance);
+    }
+
+    /// Resets the *least recently used* evictable instances, as long as the
+    /// usage exceeds the limit. If another thread is enforcing the budget
+    /// right now, this function returns immediately, and that thread enforces
+    /// the budget *again*, once it has finished.
+    pub(crate) fn enforce(&self) {
+        self.pending.store(true, Ordering::SeqCst);
+        while self.pending.load(Ordering::SeqCst) && !self.enforcing.swap(true, Ordering::SeqCst) {
+            self.pending.store(false, Ordering::SeqCst);
+            self.evict_least_recently_used();
+            self.enforcing.store(false, Ordering::SeqCst);
+        }
+    }
+
+    /// Resets the *least recently used* evictable instances, until the usage
+    /// is within the limit again, or **no** candidates are left.
+    fn evict_least_recently_used(&self) {
+        let mut candidates = self.lock().clone();
+        let mut usage: usize = candidates.iter().map(|entry| entry.usage().0).sum();
+        let limit = self.limit();
+        if usage > limit {
+            candidates.retain(|entry| entry.is_evictable() && entry.usage().0 > 0);
+            candidates.sort_by_key(|entry| entry.usage().1);
+            for candidate in candidates {
+                if usage <= limit {
+                    break;
+                }
+                let size = candidate.usage().0;
+                if candidate.try_evict() {
+                    usage = usage.saturating_sub(size);
+                }
+            }
+        }
+    }
+
+    fn tick(&self) -> u64 {
+        self.clock.fetch_add(1, Ordering::Relaxed)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Vec<&'static dyn Budgeted>> {
+        self.entries.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Debug for MemoryBudget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "MemoryBudget {{ limit: {:?}, usage: {:?} }}", self.limit(), self.usage())
+    }
+}
+
+impl<T: ?Sized> BudgetLink<T> {
+    pub const fn new() -> Self {
+        Self {
+            link: OnceLock::new(),
+            size: AtomicUsize::new(0),
+            last_access: AtomicU64::new(0),
+        }
+    }
+
+    /// Links this instance to `budget`, returning `false`, if it was linked
+    /// to a budget already.
+    pub fn link<F>(&self, budget: &'static MemoryBudget, estimate_fn: F) -> bool
+    where
+        F: Fn(&T) -> usize + Send + Sync + 'static,
+    {
+        self.link.set((budget, Box::new(estimate_fn))).is_ok()
+    }
+
+    pub fn budget(&self) -> Option<&'static MemoryBudget> {
+        self.link.get().map(|(budget, _)| *budget)
+    }
+
+    pub fn size(&self) -> usize {
+        self.size.load(Ordering::Acquire)
+    }
+
+    pub fn last_access(&self) -> u64 {
+        self.last_access.load(Ordering::Relaxed)
+    }
+
+    /// Records an access right now, if linked to a budget.
+    pub fn touch(&self) {
+        if let Some((budget, _)) = self.link.get() {
+            self.last_access.store(budget.tick(), Ordering::Relaxed);
+        }
+    }
+
+    /// Records the new "inner" value (or `None`) and enforces the budget, if
+    /// linked to a budget; must be called while the "inner" value is locked!
+    pub fn resize(&self, value: Option<&T>) {
+        if let Some((budget, estimate_fn)) = self.link.get() {
+            self.size.store(value.map_or(0, estimate_fn), Ordering::Release);
+            if value.is_some() {
+                self.last_access.store(budget.tick(), Ordering::Relaxed);
+            }
+            budget.enforce();
+        }
+    }
+}
@@ -0,0 +1,61 @@
+/*
+ * lazy_rc - Rc<T> and Arc<T> with *lazy* initialization
+ * This is free and unencumbered software released into the public domain.
+ */
+use std::io::{Error as IoError, ErrorKind, Result as IoResult};
+use std::time::{Duration, Instant};
+
+/// A token that is passed to a *cancellable* initializer, which allows the
+/// initializer to abort cooperatively once the caller has given up waiting
+///
+/// See [`LazyArc::with_cancellable_default_init()`](crate::LazyArc::with_cancellable_default_init)
+/// and [`LazyArc::or_try_init_timeout()`](crate::LazyArc::or_try_init_timeout)
+/// for details!
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CancellationToken {
+    deadline: Option<Instant>,
+}
+
+impl CancellationToken {
+    /// Create a new token that is **never** cancelled.
+    pub(crate) const fn new() -> Self {
+        Self { deadline: None }
+    }
+
+    /// Create a new token that is cancelled at `deadline`, or that is
+    /// **never** cancelled, if the deadline is `None`.
+    pub(crate) const fn with_deadline(deadline: Option<Instant>) -> Self {
+        Self { deadline }
+    }
+
+    /// Returns `true`, if and only if the initialization has been cancelled,
+    /// i.e. the initializer should abort as soon as possible.
+    pub fn is_cancelled(&self) -> bool {
+        self.deadline.is_some_and(|deadline| Instant::now() >= deadline)
+    }
+
+    /// Returns the deadline at which the initialization will be cancelled, or
+    /// `None`, if it will **never** be cancelled.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// Returns the remaining time until the initialization will be cancelled,
+    /// or `None`, if it will **never** be cancelled.
+    /// 
+    /// This is useful to bound a blocking operation, e.g. via
+    /// [`TcpStream::connect_timeout()`](std::net::TcpStream::connect_timeout).
+    pub fn remaining(&self) -> Option<Duration> {
+        self.deadline.map(|deadline| deadline.saturating_duration_since(Instant::now()))
+    }
+
+    /// Returns an error of kind [`TimedOut`](ErrorKind::TimedOut), if the
+    /// initialization has been cancelled, so that the initializer can simply
+    /// abort via the `?` operator.
+    pub fn check(&self) -> IoResult<()> {
+        match self.is_cancelled() {
+            true => Err(IoError::new(ErrorKind::TimedOut, "Initialization has been cancelled!")),
+            false => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,556 @@
+/*
+ * lazy_rc - Rc<T> and Arc<T> with *lazy* initialization
+ * This is free and unencumbered software released into the public domain.
+ */
+use std::cell::{Ref, RefCell, RefMut};
+use std::ops::{Deref, DerefMut};
+use std::rc::{self, Rc};
+use std::sync::{self, Arc, Mutex, MutexGuard, RwLock, RwLockReadGuard, RwLockWriteGuard, TryLockError};
+
+use crate::state::{Status, SyncStatus};
+use sealed::Sealed;
+
+/// A family of reference-counting pointer types, such as [`Arc<T>`] or
+/// [`Rc<T>`], which a [`Lazy<T, P, L>`](crate::Lazy) uses to share its
+/// "inner" value
+///
+/// Implement this trait for a marker type in order to plug a third-party
+/// pointer type, e.g. `triomphe::Arc<T>`, into a `Lazy<T, P, L>`.
+pub trait PointerFamily {
+    /// The name of a [`Lazy<T, P, L>`](crate::Lazy) that uses this pointer
+    /// family, as shown by its `Debug` output, e.g. `"LazyArc"`.
+    const LAZY_NAME: &'static str = "Lazy";
+
+    /// The pointer type, e.g. `Arc<T>`.
+    type Pointer<T: ?Sized>: Clone + Deref<Target = T>;
+
+    /// Creates a new pointer to `value`.
+    fn new<T>(value: T) -> Self::Pointer<T>;
+
+    /// Returns the value, if `pointer` is the *only* pointer to the value.
+    /// Otherwise, `pointer` is returned as the error.
+    fn try_unwrap<T>(pointer: Self::Pointer<T>) -> Result<T, Self::Pointer<T>>;
+
+    /// Returns a mutable reference to the value, if `pointer` is the *only*
+    /// pointer to the value.
+    fn get_mut<T: ?Sized>(pointer: &mut Self::Pointer<T>) -> Option<&mut T>;
+
+    /// Returns a mutable reference to the value, *cloning* the value first,
+    /// if there are other pointers to the value.
+    fn make_mut<T: Clone>(pointer: &mut Self::Pointer<T>) -> &mut T;
+
+    /// Returns `true`, if and only if both pointers point to the same value.
+    fn ptr_eq<T: ?Sized>(a: &Self::Pointer<T>, b: &Self::Pointer<T>) -> bool;
+
+    /// Returns the number of *strong* pointers to the value, which does
+    /// **not** include any *weak* pointers.
+    fn strong_count<T: ?Sized>(pointer: &Self::Pointer<T>) -> usize;
+}
+
+/// A [`PointerFamily`] that supports *weak* pointers, which allows a value to
+/// hold a reference to itself, see
+/// [`Lazy::or_init_cyclic()`](crate::Lazy::or_init_cyclic)
+pub trait CyclicFamily: PointerFamily {
+    /// The weak pointer type, e.g. `sync::Weak<T>`.
+    type Weak<T>;
+
+    /// Creates a new pointer to the value that is returned by `init_fn()`,
+    /// which is called with a weak pointer to the value being constructed.
+    fn new_cyclic<T, F>(init_fn: F) -> Self::Pointer<T>
+    where
+        F: FnOnce(&Self::Weak<T>) -> T;
+}
+
+/// A family of lock types, such as [`RwLock<T>`] or [`RefCell<T>`], which a
+/// [`Lazy<T, P, L>`](crate::Lazy) uses to protect its "inner" value
+///
+/// Implement this trait for a marker type in order to plug a third-party lock
+/// type, e.g. `parking_lot::Mutex<T>`, into a `Lazy<T, P, L>`. A *poisoned*
+/// lock is expected to panic, rather than being reported as an error.
+pub trait LockFamily {
+    /// The lock type, e.g. `RwLock<V>`.
+    type Lock<V>;
+    /// The guard that provides *shared* access to the locked value.
+    type ReadGuard<'a, V: 'a>: Deref<Target = V>;
+    /// The guard that provides *exclusive* access to the locked value.
+    type WriteGuard<'a, V: 'a>: DerefMut<Target = V>;
+    /// The guard that provides *upgradable* access to the locked value, i.e.
+    /// shared access that can be upgraded to exclusive access. A lock type
+    /// that does **not** support upgradable access uses its write guard here.
+    type UpgradableGuard<'a, V: 'a>: Deref<Target = V>;
+    /// The [threading model](Threading) of the lock type, i.e. [`ThreadSafe`]
+    /// or [`SingleThreaded`].
+    type Threading: Threading;
+
+    /// Creates a new lock that contains `value`.
+    fn new<V>(value: V) -> Self::Lock<V>;
+
+    /// Acquires *shared* access, blocking the current thread as needed.
+    fn read<V>(lock: &Self::Lock<V>) -> Self::ReadGuard<'_, V>;
+
+    /// Acquires *exclusive* access, blocking the current thread as needed.
+    fn write<V>(lock: &Self::Lock<V>) -> Self::WriteGuard<'_, V>;
+
+    /// Acquires *upgradable* access, blocking the current thread as needed.
+    ///
+    /// This is used by the check-then-initialize path, so that other readers
+    /// are **not** blocked until the value is actually going to be written.
+    fn upgradable_read<V>(lock: &Self::Lock<V>) -> Self::UpgradableGuard<'_, V>;
+
+    /// Upgrades *upgradable* access to *exclusive* access.
+    fn upgrade<'a, V: 'a>(guard: Self::UpgradableGuard<'a, V>) -> Self::WriteGuard<'a, V>;
+
+    /// Acquires *shared* access, if that is possible without blocking.
+    fn try_read<V>(lock: &Self::Lock<V>) -> Option<Self::ReadGuard<'_, V>>;
+
+    /// Acquires *exclusive* access, if that is possible without blocking.
+    fn try_write<V>(lock: &Self::Lock<V>) -> Option<Self::WriteGuard<'_, V>>;
+
+    /// Returns a mutable reference to the locked value.
+    fn get_mut<V>(lock: &mut Self::Lock<V>) -> &mut V;
+
+    /// Consumes the lock, returning the locked value.
+    fn into_inner<V>(lock: Self::Lock<V>) -> V;
+}
+
+/// The *threading model* of a [`LockFamily`], i.e. either [`ThreadSafe`] or
+/// [`SingleThreaded`]
+///
+/// The threading model determines whether the *default* initializer of a
+/// [`Lazy<T, P, L>`](crate::Lazy) must be `Send` and `Sync`, and how the
+/// instance synchronizes its own bookkeeping, e.g. its [state](crate::State).
+/// This trait is *sealed*, i.e. it can **not** be implemented outside of this
+/// crate.
+pub trait Threading: Sealed {
+    /// A boxed initializer that is called with an argument of type `A`, which
+    /// is `Send` and `Sync`, if and only if the threading model is thread-safe.
+    type Init<A: ?Sized, V>: ?Sized + Fn(&A) -> V;
+    /// The type that must be `Send` and `Sync`, so that a function of type
+    /// `U` can be boxed as an initializer, i.e. `U` itself, if the threading
+    /// model is thread-safe, or `()` otherwise.
+    type Captured<U>;
+
+    /// Boxes the initializer `init`, which is invoked via `call`.
+    fn box_init<A: ?Sized, U, V, C>(init: U, call: C) -> Box<Self::Init<A, V>>
+    where
+        U: 'static,
+        C: Fn(&U, &A) -> V + Send + Sync + 'static,
+        Self::Captured<U>: Send + Sync;
+
+    /// Boxes the initializer `init`, which is `Send` and `Sync` itself.
+    fn box_sync_init<A: ?Sized, V, F>(init: F) -> Box<Self::Init<A, V>>
+    where
+        F: Fn(&A) -> V + Send + Sync + 'static;
+}
+
+/// The type that must be `Send` and `Sync`, so that a function of type `U` can
+/// be used as the *default* initializer of a `Lazy<T, P, L>`, see
+/// [`Threading::Captured`].
+pub(crate) type Captured<L, U> = <<L as LockFamily>::Threading as Threading>::Captured<U>;
+
+pub(crate) mod sealed {
+    use crate::state::StatusCell;
+
+    /// The bookkeeping of a [`Lazy<T, P, L>`](crate::Lazy), which is
+    /// synchronized according to its threading model.
+    pub trait Sealed {
+        /// The container of the current status, see [`State`](crate::State).
+        type Status: StatusCell;
+        /// A container that protects a value of type `V`, e.g. a `Mutex<V>`.
+        type Cell<V>;
+
+        fn new_status() -> Self::Status;
+
+        fn new_cell<V>(value: V) -> Self::Cell<V>;
+
+        fn with_cell<V, R, F: FnOnce(&mut V) -> R>(cell: &Self::Cell<V>, access_fn: F) -> R;
+    }
+}
+
+/// The [`PointerFamily`] of the thread-safe [`Arc<T>`]
+#[derive(Debug)]
+pub enum ArcFamily { }
+
+/// The [`PointerFamily`] of the single-threaded [`Rc<T>`]
+#[derive(Debug)]
+pub enum RcFamily { }
+
+/// The [`LockFamily`] of the thread-safe [`RwLock<T>`], which allows
+/// concurrent readers
+#[derive(Debug)]
+pub enum RwLockFamily { }
+
+/// The [`LockFamily`] of the thread-safe [`Mutex<T>`], which is cheaper than
+/// a `RwLock<T>`, but serializes all readers
+#[derive(Debug)]
+pub enum MutexFamily { }
+
+/// The [`LockFamily`] of the single-threaded [`RefCell<T>`], which panics
+/// instead of blocking, e.g. if accessed from within the initializer
+#[derive(Debug)]
+pub enum RefCellFamily { }
+
+/// The [`LockFamily`] of the thread-safe `parking_lot::RwLock<T>`, which
+/// supports *upgradable* reads and which is **never** poisoned
+///
+/// This type is only available with the `parking_lot` feature enabled.
+#[cfg(feature = "parking_lot")]
+#[derive(Debug)]
+pub enum ParkingLotFamily { }
+
+/// The [`Threading`] model of a lock type that can be shared by multiple
+/// threads, e.g. [`RwLock<T>`]
+#[derive(Debug)]
+pub enum ThreadSafe { }
+
+/// The [`Threading`] model of a lock type that is confined to a single thread,
+/// e.g. [`RefCell<T>`]
+#[derive(Debug)]
+pub enum SingleThreaded { }
+
+impl PointerFamily for ArcFamily {
+    const LAZY_NAME: &'static str = "LazyArc";
+
+    type Pointer<T: ?Sized> = Arc<T>;
+
+    fn new<T>(value: T) -> Arc<T> {
+        Arc::new(value)
+    }
+
+    fn try_unwrap<T>(pointer: Arc<T>) -> Result<T, Arc<T>> {
+        Arc::try_unwrap(pointer)
+    }
+
+    fn get_mut<T: ?Sized>(pointer: &mut Arc<T>) -> Option<&mut T> {
+        Arc::get_mut(pointer)
+    }
+
+    fn make_mut<T: Clone>(pointer: &mut Arc<T>) -> &mut T {
+        Arc::make_mut(pointer)
+    }
+
+    fn ptr_eq<T: ?Sized>(a: &Arc<T>, b: &Arc<T>) -> bool {
+        Arc::ptr_eq(a, b)
+    }
+
+    fn strong_count<T: ?Sized>(pointer: &Arc<T>) -> usize {
+        Arc::strong_count(pointer)
+    }
+}
+
+impl CyclicFamily for ArcFamily {
+    type Weak<T> = sync::Weak<T>;
+
+    fn new_cyclic<T, F>(init_fn: F) -> Arc<T>
+    where
+        F: FnOnce(&sync::Weak<T>) -> T,
+    {
+        Arc::new_cyclic(init_fn)
+    }
+}
+
+impl PointerFamily for RcFamily {
+    const LAZY_NAME: &'static str = "LazyRc";
+
+    type Pointer<T: ?Sized> = Rc<T>;
+
+    fn new<T>(value: T) -> Rc<T> {
+        Rc::new(value)
+    }
+
+    fn try_unwrap<T>(pointer: Rc<T>) -> Result<T, Rc<T>> {
+        Rc::try_unwrap(pointer)
+    }
+
+    fn get_mut<T: ?Sized>(pointer: &mut Rc<T>) -> Option<&mut T> {
+        Rc::get_mut(pointer)
+    }
+
+    fn make_mut<T: Clone>(pointer: &mut Rc<T>) -> &mut T {
+        Rc::make_mut(pointer)
+    }
+
+    fn ptr_eq<T: ?Sized>(a: &Rc<T>, b: &Rc<T>) -> bool {
+        Rc::ptr_eq(a, b)
+    }
+
+    fn strong_count<T: ?Sized>(pointer: &Rc<T>) -> usize {
+        Rc::strong_count(pointer)
+    }
+}
+
+impl CyclicFamily for RcFamily {
+    type Weak<T> = rc::Weak<T>;
+
+    fn new_cyclic<T, F>(init_fn: F) -> Rc<T>
+    where
+        F: FnOnce(&rc::Weak<T>) -> T,
+    {
+        Rc::new_cyclic(init_fn)
+    }
+}
+
+impl LockFamily for RwLockFamily {
+    type Lock<V> = RwLock<V>;
+    type ReadGuard<'a, V: 'a> = RwLockReadGuard<'a, V>;
+    type WriteGuard<'a, V: 'a> = RwLockWriteGuard<'a, V>;
+    type UpgradableGuard<'a, V: 'a> = RwLockWriteGuard<'a, V>;
+    type Threading = ThreadSafe;
+
+    fn new<V>(value: V) -> RwLock<V> {
+        RwLock::new(value)
+    }
+
+    fn read<V>(lock: &RwLock<V>) -> RwLockReadGuard<'_, V> {
+        lock.read().unwrap()
+    }
+
+    fn write<V>(lock: &RwLock<V>) -> RwLockWriteGuard<'_, V> {
+        lock.write().unwrap()
+    }
+
+    fn upgradable_read<V>(lock: &RwLock<V>) -> RwLockWriteGuard<'_, V> {
+        Self::write(lock)
+    }
+
+    fn upgrade<'a, V: 'a>(guard: RwLockWriteGuard<'a, V>) -> RwLockWriteGuard<'a, V> {
+        guard
+    }
+
+    fn try_read<V>(lock: &RwLock<V>) -> Option<RwLockReadGuard<'_, V>> {
+        match lock.try_read() {
+            Ok(guard) => Some(guard),
+            Err(TryLockError::WouldBlock) => None,
+            Err(TryLockError::Poisoned(error)) => panic!("{}", error),
+        }
+    }
+
+    fn try_write<V>(lock: &RwLock<V>) -> Option<RwLockWriteGuard<'_, V>> {
+        match lock.try_write() {
+            Ok(guard) => Some(guard),
+            Err(TryLockError::WouldBlock) => None,
+            Err(TryLockError::Poisoned(error)) => panic!("{}", error),
+        }
+    }
+
+    fn get_mut<V>(lock: &mut RwLock<V>) -> &mut V {
+        lock.get_mut().unwrap()
+    }
+
+    fn into_inner<V>(lock: RwLock<V>) -> V {
+        lock.into_inner().unwrap()
+    }
+}
+
+impl LockFamily for MutexFamily {
+    type Lock<V> = Mutex<V>;
+    type ReadGuard<'a, V: 'a> = MutexGuard<'a, V>;
+    type WriteGuard<'a, V: 'a> = MutexGuard<'a, V>;
+    type UpgradableGuard<'a, V: 'a> = MutexGuard<'a, V>;
+    type Threading = ThreadSafe;
+
+    fn new<V>(value: V) -> Mutex<V> {
+        Mutex::new(value)
+    }
+
+    fn read<V>(lock: &Mutex<V>) -> MutexGuard<'_, V> {
+        lock.lock().unwrap()
+    }
+
+    fn write<V>(lock: &Mutex<V>) -> MutexGuard<'_, V> {
+        lock.lock().unwrap()
+    }
+
+    fn upgradable_read<V>(lock: &Mutex<V>) -> MutexGuard<'_, V> {
+        Self::write(lock)
+    }
+
+    fn upgrade<'a, V: 'a>(guard: MutexGuard<'a, V>) -> MutexGuard<'a, V> {
+        guard
+    }
+
+    fn try_read<V>(lock: &Mutex<V>) -> Option<MutexGuard<'_, V>> {
+        Self::try_write(lock)
+    }
+
+    fn try_write<V>(lock: &Mutex<V>) -> Option<MutexGuard<'_, V>> {
+        match lock.try_lock() {
+            Ok(guard) => Some(guard),
+            Err(TryLockError::WouldBlock) => None,
+            Err(TryLockError::Poisoned(error)) => panic!("{}", error),
+        }
+    }
+
+    fn get_mut<V>(lock: &mut Mutex<V>) -> &mut V {
+        lock.get_mut().unwrap()
+    }
+
+    fn into_inner<V>(lock: Mutex<V>) -> V {
+        lock.into_inner().unwrap()
+    }
+}
+
+impl LockFamily for RefCellFamily {
+    type Lock<V> = RefCell<V>;
+    type ReadGuard<'a, V: 'a> = Ref<'a, V>;
+    type WriteGuard<'a, V: 'a> = RefMut<'a, V>;
+    type UpgradableGuard<'a, V: 'a> = RefMut<'a, V>;
+    type Threading = SingleThreaded;
+
+    fn new<V>(value: V) -> RefCell<V> {
+        RefCell::new(value)
+    }
+
+    fn read<V>(lock: &RefCell<V>) -> Ref<'_, V> {
+        lock.borrow()
+    }
+
+    fn write<V>(lock: &RefCell<V>) -> RefMut<'_, V> {
+        lock.borrow_mut()
+    }
+
+    fn upgradable_read<V>(lock: &RefCell<V>) -> RefMut<'_, V> {
+        Self::write(lock)
+    }
+
+    fn upgrade<'a, V: 'a>(guard: RefMut<'a, V>) -> RefMut<'a, V> {
+        guard
+    }
+
+    fn try_read<V>(lock: &RefCell<V>) -> Option<Ref<'_, V>> {
+        lock.try_borrow().ok()
+    }
+
+    fn try_write<V>(lock: &RefCell<V>) -> Option<RefMut<'_, V>> {
+        lock.try_borrow_mut().ok()
+    }
+
+    fn get_mut<V>(lock: &mut RefCell<V>) -> &mut V {
+        lock.get_mut()
+    }
+
+    fn into_inner<V>(lock: RefCell<V>) -> V {
+        lock.into_inner()
+    }
+}
+
+#[cfg(feature = "parking_lot")]
+impl LockFamily for ParkingLotFamily {
+    type Lock<V> = parking_lot::RwLock<V>;
+    type ReadGuard<'a, V: 'a> = parking_lot::RwLockReadGuard<'a, V>;
+    type WriteGuard<'a, V: 'a> = parking_lot::RwLockWriteGuard<'a, V>;
+    type UpgradableGuard<'a, V: 'a> = parking_lot::RwLockUpgradableReadGuard<'a, V>;
+    type Threading = ThreadSafe;
+
+    fn new<V>(value: V) -> parking_lot::RwLock<V> {
+        parking_lot::RwLock::new(value)
+    }
+
+    fn read<V>(lock: &parking_lot::RwLock<V>) -> parking_lot::RwLockReadGuard<'_, V> {
+        lock.read()
+    }
+
+    fn write<V>(lock: &parking_lot::RwLock<V>) -> parking_lot::RwLockWriteGuard<'_, V> {
+        lock.write()
+    }
+
+    fn upgradable_read<V>(lock: &parking_lot::RwLock<V>) -> parking_lot::RwLockUpgradableReadGuard<'_, V> {
+        lock.upgradable_read()
+    }
+
+    fn upgrade<'a, V: 'a>(guard: parking_lot::RwLockUpgradableReadGuard<'a, V>) -> parking_lot::RwLockWriteGuard<'a, V> {
+        parking_lot::RwLockUpgradableReadGuard::upgrade(guard)
+    }
+
+    fn try_read<V>(lock: &parking_lot::RwLock<V>) -> Option<parking_lot::RwLockReadGuard<'_, V>> {
+        lock.try_read()
+    }
+
+    fn try_write<V>(lock: &parking_lot::RwLock<V>) -> Option<parking_lot::RwLockWriteGuard<'_, V>> {
+        lock.try_write()
+    }
+
+    fn get_mut<V>(lock: &mut parking_lot::RwLock<V>) -> &mut V {
+        lock.get_mut()
+    }
+
+    fn into_inner<V>(lock: parking_lot::RwLock<V>) -> V {
+        lock.into_inner()
+    }
+}
+
+impl Threading for ThreadSafe {
+    type Init<A: ?Sized, V> = dyn Fn(&A) -> V + Send + Sync;
+    type Captured<U> = U;
+
+    fn box_init<A: ?Sized, U, V, C>(init: U, call: C) -> Box<Self::Init<A, V>>
+    where
+        U: 'static,
+        C: Fn(&U, &A) -> V + Send + Sync + 'static,
+        Self::Captured<U>: Send + Sync,
+    {
+        Box::new(move |arg| call(&init, arg))
+    }
+
+    fn box_sync_init<A: ?Sized, V, F>(init: F) -> Box<Self::Init<A, V>>
+    where
+        F: Fn(&A) -> V + Send + Sync + 'static,
+    {
+        Box::new(init)
+    }
+}
+
+impl Threading for SingleThreaded {
+    type Init<A: ?Sized, V> = dyn Fn(&A) -> V;
+    type Captured<U> = ();
+
+    fn box_init<A: ?Sized, U, V, C>(init: U, call: C) -> Box<Self::Init<A, V>>
+    where
+        U: 'static,
+        C: Fn(&U, &A) -> V + Send + Sync + 'static,
+        Self::Captured<U>: Send + Sync,
+    {
+        Box::new(move |arg| call(&init, arg))
+    }
+
+    fn box_sync_init<A: ?Sized, V, F>(init: F) -> Box<Self::Init<A, V>>
+    where
+        F: Fn(&A) -> V + Send + Sync + 'static,
+    {
+        Box::new(init)
+    }
+}
+
+impl Sealed for ThreadSafe {
+    type Status = SyncStatus;
+    type Cell<V> = Mutex<V>;
+
+    fn new_status() -> SyncStatus {
+        SyncStatus::new()
+    }
+
+    fn new_cell<V>(value: V) -> Mutex<V> {
+        Mutex::new(value)
+    }
+
+    fn with_cell<V, R, F: FnOnce(&mut V) -> R>(cell: &Mutex<V>, access_fn: F) -> R {
+        access_fn(&mut cell.lock().unwrap_or_else(|poisoned| poisoned.into_inner()))
+    }
+}
+
+impl Sealed for SingleThreaded {
+    type Status = RefCell<Status>;
+    type Cell<V> = RefCell<V>;
+
+    fn new_status() -> RefCell<Status> {
+        RefCell::new(Status::new())
+    }
+
+    fn new_cell<V>(value: V) -> RefCell<V> {
+        RefCell::new(value)
+    }
+
+    fn with_cell<V, R, F: FnOnce(&mut V) -> R>(cell: &RefCell<V>, access_fn: F) -> R {
+        access_fn(&mut cell.borrow_mut())
+    }
+}
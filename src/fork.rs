@@ -0,0 +1,157 @@
+/*
+ * lazy_rc - Rc<T> and Arc<T> with *lazy* initialization
+ * This is free and unencumbered software released into the public domain.
+ */
+use std::cell::RefCell;
+use std::ptr;
+use std::sync::{Mutex, MutexGuard, Once};
+
+type Registry = Vec<(&'static dyn Fork, ForkPolicy)>;
+type LockedInstances = Vec<(Box<dyn Locked>, ForkPolicy)>;
+type Prepared = (MutexGuard<'static, Registry>, LockedInstances);
+
+/// The behavior of a [`Lazy<T, P, L>`](crate::Lazy) in the *child* process
+/// after a `fork()`, see
+/// [`Lazy::set_fork_policy()`](crate::Lazy::set_fork_policy)
+///
+/// Regardless of the policy, the "inner" value is locked while forking, so a
+/// pending initialization completes *before* the process is forked and the
+/// child never inherits a lock that is held by a thread that does **not**
+/// exist in the child. The instances are locked *without* blocking: if any
+/// instance is busy, all locks are released again, until the pending work,
+/// which may depend on other registered instances, has completed.
+///
+/// Note: There is **no** upper bound on how long a `fork()` waits. A thread
+/// that holds *shared* access for a long time, e.g. inside of
+/// [`map()`](crate::Lazy::map), delays the `fork()` accordingly. Therefore,
+/// `fork()` must **not** be called from inside of `map()` on a registered
+/// instance, or it deadlocks.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ForkPolicy {
+    /// The child keeps the "inner" value that was copied from the parent.
+    #[default]
+    Keep,
+    /// The "inner" value is reset in the child, so that the next access in
+    /// the child initializes it again. The copied value is *leaked*, rather
+    /// than dropped, because its `Drop` impl may **not** work in the child,
+    /// e.g. if it joins threads that only exist in the parent.
+    ResetInChild,
+    /// The "inner" value is reset in the child, like with `ResetInChild`, and
+    /// then re-initialized on the *first* access in the child, using the
+    /// *default* initializer, even by functions that otherwise never
+    /// initialize, such as [`value()`](crate::Lazy::value). If there is **no**
+    /// *default* initializer, or if it fails, the "inner" value remains
+    /// *uninitialized* in the child.
+    ReinitializeInChild,
+}
+
+/// The result of [`Fork::try_lock()`].
+pub(crate) enum Attempt {
+    /// The instance has been locked.
+    Locked(Box<dyn Locked>),
+    /// The current thread is initializing the instance, so it can **not** be
+    /// locked at all.
+    Skipped,
+    /// The instance is locked by another thread right now.
+    Busy,
+}
+
+/// An instance that can be locked while forking.
+pub(crate) trait Fork: Sync {
+    /// Tries to lock this instance before forking, without blocking.
+    fn try_lock(&'static self) -> Attempt;
+
+    /// Waits for this instance to become available, e.g. until a pending
+    /// initialization by another thread has completed, *without* locking it.
+    fn wait(&self);
+}
+
+/// A locked instance, which is unlocked when dropped.
+pub(crate) trait Locked {
+    /// Applies `policy` in the child process, then unlocks the instance.
+    fn child(self: Box<Self>, policy: ForkPolicy);
+}
+
+static REGISTRY: Mutex<Registry> = Mutex::new(Vec::new());
+
+thread_local! {
+    static PREPARED: RefCell<Option<Prepared>> = const { RefCell::new(None) };
+}
+
+/// Sets the fork `policy` of `instance`, installing the `pthread_atfork`
+/// handlers, if not done yet.
+pub(crate) fn register(instance: &'static dyn Fork, policy: ForkPolicy) {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let result = unsafe { libc::pthread_atfork(Some(prepare), Some(parent), Some(child)) };
+        assert_eq!(result, 0, "Failed to install the fork handlers!");
+    });
+    let mut registry = lock();
+    match registry.iter_mut().find(|(existing, _)| ptr::addr_eq(*existing, instance)) {
+        Some(entry) => entry.1 = policy,
+        None => registry.push((instance, policy)),
+    }
+}
+
+/// Locks all registered instances, *before* the process is forked.
+///
+/// The registry is **not** locked while locking the instances, because an
+/// initializer may register another instance. It is locked only once all
+/// instances are locked, and retained until after the `fork()`.
+extern "C" fn prepare() {
+    let locks = loop {
+        let instances = lock().clone();
+        match try_lock_all(&instances) {
+            Ok(locked) => {
+                let registry = lock();
+                if is_unchanged(&registry, &instances) {
+                    break (registry, locked);
+                }
+            },
+            Err(busy) => busy.wait(),
+        }
+    };
+    PREPARED.with(|prepared| *prepared.borrow_mut() = Some(locks));
+}
+
+/// Unlocks all registered instances in the parent process.
+extern "C" fn parent() {
+    PREPARED.with(|prepared| drop(prepared.borrow_mut().take()));
+}
+
+/// Applies the policies and unlocks all registered instances in the child
+/// process.
+extern "C" fn child() {
+    if let Some((registry, locked)) = PREPARED.with(|prepared| prepared.borrow_mut().take()) {
+        drop(registry);
+        for (locked, policy) in locked {
+            locked.child(policy);
+        }
+    }
+}
+
+/// Locks all `instances`, or none of them, if any instance is busy. In the
+/// latter case, the busy instance is returned.
+fn try_lock_all(instances: &Registry) -> Result<LockedInstances, &'static dyn Fork> {
+    let mut locked = Vec::with_capacity(instances.len());
+    for (instance, policy) in instances {
+        match instance.try_lock() {
+            Attempt::Locked(guard) => locked.push((guard, *policy)),
+            Attempt::Skipped => {},
+            Attempt::Busy => return Err(*instance),
+        }
+    }
+    Ok(locked)
+}
+
+/// Returns `true`, if **no** instance has been registered or changed its
+/// policy since `instances` was copied from the `registry`.
+fn is_unchanged(registry: &Registry, instances: &Registry) -> bool {
+    registry.len() == instances.len() && registry.iter().zip(instances).all(|((a, policy_a), (b, policy_b))| {
+        ptr::addr_eq(*a, *b) && policy_a == policy_b
+    })
+}
+
+fn lock() -> MutexGuard<'static, Registry> {
+    REGISTRY.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
@@ -0,0 +1,84 @@
+/*
+ * lazy_rc - Rc<T> and Arc<T> with *lazy* initialization
+ * This is free and unencumbered software released into the public domain.
+ */
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+static EPOCH: OnceLock<Instant> = OnceLock::new();
+
+/// The idle timeout of a [`Lazy<T, P, L>`](crate::Lazy), along with the time
+/// of the most recent access.
+///
+/// With the `test-util` feature enabled, the clock of each timer can be
+/// advanced *manually*, so that tests do **not** need to sleep.
+pub(crate) struct IdleTimer {
+    timeout: AtomicU64,
+    last_access: AtomicU64,
+    #[cfg(feature = "test-util")]
+    skew: AtomicU64,
+}
+
+impl IdleTimer {
+    pub const fn new() -> Self {
+        Self {
+            timeout: AtomicU64::new(0),
+            last_access: AtomicU64::new(0),
+            #[cfg(feature = "test-util")]
+            skew: AtomicU64::new(0),
+        }
+    }
+
+    pub fn timeout(&self) -> Option<Duration> {
+        match self.timeout.load(Ordering::Relaxed) {
+            0 => None,
+            nanos => Some(Duration::from_nanos(nanos)),
+        }
+    }
+
+    pub fn set_timeout(&self, timeout: Option<Duration>) {
+        let nanos = timeout.map_or(0, |timeout| nanos(timeout).max(1));
+        self.timeout.store(nanos, Ordering::Relaxed);
+    }
+
+    /// Records an access right now, or that the value was initialized.
+    pub fn touch(&self) {
+        if self.timeout.load(Ordering::Relaxed) != 0 {
+            self.last_access.store(self.now(), Ordering::Release);
+        }
+    }
+
+    /// Returns `true`, if the most recent access was longer ago than the
+    /// idle timeout.
+    pub fn is_expired(&self) -> bool {
+        match (self.timeout.load(Ordering::Relaxed), self.last_access.load(Ordering::Acquire)) {
+            (0, _) | (_, 0) => false,
+            (timeout, last_access) => self.now().saturating_sub(last_access) > timeout,
+        }
+    }
+
+    /// Advances the clock of this timer by `duration`.
+    #[cfg(feature = "test-util")]
+    pub fn advance(&self, duration: Duration) {
+        self.skew.fetch_add(nanos(duration), Ordering::AcqRel);
+    }
+
+    /// Returns the current time of this timer in nanoseconds.
+    fn now(&self) -> u64 {
+        #[cfg(feature = "test-util")]
+        let skew = self.skew.load(Ordering::Acquire);
+        #[cfg(not(feature = "test-util"))]
+        let skew = 0;
+        now().saturating_add(skew)
+    }
+}
+
+/// Returns the current time in nanoseconds, which is never zero.
+fn now() -> u64 {
+    nanos(EPOCH.get_or_init(Instant::now).elapsed()).saturating_add(1)
+}
+
+fn nanos(duration: Duration) -> u64 {
+    u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX)
+}
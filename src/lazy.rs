@@ -0,0 +1,1522 @@
+/*
+ * lazy_rc - Rc<T> and Arc<T> with *lazy* initialization
+ * This is free and unencumbered software released into the public domain.
+ */
+use std::cell::RefCell;
+use std::fmt::Debug;
+use std::future::Future;
+use std::io::{Result as IoResult};
+use std::ops::Deref;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::{Mutex, RwLock};
+#[cfg(all(unix, feature = "fork"))]
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::task::{Context, Poll};
+#[cfg(feature = "idle-timeout")]
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::{CancellationToken, InitError};
+#[cfg(feature = "memory-budget")]
+use crate::budget::{BudgetLink, Budgeted, MemoryBudget};
+use crate::family::{ArcFamily, Captured, CyclicFamily, LockFamily, MutexFamily, PointerFamily, RefCellFamily, RwLockFamily, Threading};
+#[cfg(all(unix, feature = "fork"))]
+use crate::family::ThreadSafe;
+use crate::family::sealed::Sealed;
+#[cfg(feature = "parking_lot")]
+use crate::family::ParkingLotFamily;
+#[cfg(all(unix, feature = "fork"))]
+use crate::fork::{self, Attempt, Fork, ForkPolicy, Locked};
+#[cfg(feature = "idle-timeout")]
+use crate::idle::IdleTimer;
+use crate::lazy_arc::ArcLockFamily;
+#[cfg(feature = "test-util")]
+use crate::overrides::Overrides;
+#[cfg(feature = "shutdown")]
+use crate::shutdown::{Registration, Shutdown, ShutdownOutcome};
+use crate::sources;
+use crate::state::{State, Status, StatusCell, SyncStatus, describe, track, track_infailable};
+#[cfg(feature = "stats")]
+use crate::stats::Stats;
+use crate::stats::Counters;
+use crate::subscription::{Subscribers, Subscription};
+use crate::utils::{BoxError, DefaultInit, call_infailable, or_init_with, or_try_init_with};
+#[cfg(panic = "unwind")]
+use crate::utils::try_new_cyclic;
+
+type InnerGuard<'a, T, P, L> = <L as LockFamily>::WriteGuard<'a, Option<<P as PointerFamily>::Pointer<T>>>;
+type InitFn<T, P, L> = <<L as LockFamily>::Threading as Threading>::Init<CancellationToken, Result<<P as PointerFamily>::Pointer<T>, InitError>>;
+type StatusOf<L> = <<L as LockFamily>::Threading as Sealed>::Status;
+
+/// A reference-counting pointer with ***lazy*** initialization, which is
+/// generic over the [pointer family](PointerFamily) `P` and the
+/// [lock family](LockFamily) `L`
+///
+/// Usually, you will use one of the aliases [`LazyArc<T>`](crate::LazyArc) or
+/// [`LazyRc<T>`](crate::LazyRc). Other pointer or lock types, e.g. from
+/// third-party crates, can be plugged in by implementing the `PointerFamily`
+/// or `LockFamily` trait:
+///
+/// ```
+/// use lazy_rc::{ArcFamily, Lazy, MutexFamily};
+///
+/// type LazyArcMutex<T> = Lazy<T, ArcFamily, MutexFamily>;
+///
+/// static COUNTER: LazyArcMutex<u32> = LazyArcMutex::empty();
+///
+/// assert_eq!(*COUNTER.or_init_with(|| 42), 42);
+/// ```
+///
+/// A `Lazy<T, P, L>` is [`Send`] and [`Sync`] if and only if both, the
+/// pointer `P::Pointer<T>` and the lock `L::Lock<_>`, are `Send` and `Sync`.
+pub struct Lazy<T: ?Sized, P: PointerFamily, L: LockFamily> {
+    inner: L::Lock<Option<P::Pointer<T>>>,
+    default_init: DefaultInit<InitFn<T, P, L>>,
+    generation: AtomicU64,
+    subscribers: Subscribers<T, P, L::Threading>,
+    counters: Counters,
+    status: StatusOf<L>,
+    #[cfg(feature = "idle-timeout")]
+    idle: IdleTimer,
+    #[cfg(feature = "memory-budget")]
+    budget: BudgetLink<T>,
+    #[cfg(feature = "test-util")]
+    overrides: Overrides<T, P>,
+    #[cfg(feature = "shutdown")]
+    registration: Registration,
+    #[cfg(all(unix, feature = "fork"))]
+    reinitialize: AtomicBool,
+}
+
+impl<T: ?Sized, P: PointerFamily> Lazy<T, P, RwLockFamily> {
+    /// Create a new instance that is initially *empty* and that contains
+    /// **no** *default* initializer, see [`new()`](Self::new).
+    ///
+    /// Unlike `new()`, this function can be used to initialize a **`static`**
+    /// variable.
+    pub const fn empty() -> Self {
+        Self::from_parts(RwLock::new(None), DefaultInit::None, SyncStatus::new(), Subscribers::new(Mutex::new(Vec::new())))
+    }
+}
+
+impl<T: ?Sized, P: PointerFamily> Lazy<T, P, MutexFamily> {
+    /// Create a new instance that is initially *empty* and that contains
+    /// **no** *default* initializer, see [`new()`](Self::new).
+    ///
+    /// Unlike `new()`, this function can be used to initialize a **`static`**
+    /// variable.
+    pub const fn empty() -> Self {
+        Self::from_parts(Mutex::new(None), DefaultInit::None, SyncStatus::new(), Subscribers::new(Mutex::new(Vec::new())))
+    }
+}
+
+#[cfg(feature = "parking_lot")]
+impl<T: ?Sized, P: PointerFamily> Lazy<T, P, ParkingLotFamily> {
+    /// Create a new instance that is initially *empty* and that contains
+    /// **no** *default* initializer, see [`new()`](Self::new).
+    ///
+    /// Unlike `new()`, this function can be used to initialize a **`static`**
+    /// variable.
+    pub const fn empty() -> Self {
+        Self::from_parts(parking_lot::RwLock::new(None), DefaultInit::None, SyncStatus::new(), Subscribers::new(Mutex::new(Vec::new())))
+    }
+}
+
+impl<T: ?Sized, P: PointerFamily> Lazy<T, P, RefCellFamily> {
+    /// Create a new instance that is initially *empty* and that contains
+    /// **no** *default* initializer, see [`new()`](Self::new).
+    ///
+    /// Unlike `new()`, this function can be used to initialize a
+    /// [`thread_local!`](std::thread_local) variable in a `const` block.
+    pub const fn empty() -> Self {
+        Self::from_parts(RefCell::new(None), DefaultInit::None, RefCell::new(Status::new()), Subscribers::new(RefCell::new(Vec::new())))
+    }
+}
+
+impl<T: ?Sized, P: PointerFamily, L: LockFamily> Lazy<T, P, L> {
+    /// Create a new instance that is initially *empty* and that contains
+    /// **no** *default* initializer.
+    ///
+    /// The "inner" value will be [initialized](Self::or_init_with()) on first
+    /// access. Default initialization is **not** supported by this instance!
+    pub fn new() -> Self {
+        Self::with_default(DefaultInit::None)
+    }
+
+    /// Create a new instance that is initially *empty* and that contains the
+    /// given *default* initializer.
+    ///
+    /// The "inner" value will be [initialized](Self::or_init_with()) on first
+    /// access. Default initialization *is* supported by this instance.
+    pub fn with_default_init<U>(default_init: U) -> Self
+    where
+        T: Sized,
+        U: Fn() -> T + 'static,
+        Captured<L, U>: Send + Sync,
+    {
+        Self::with_default(DefaultInit::Infailable(L::Threading::box_init(default_init, |init, _| Ok(P::new(init())))))
+    }
+
+    /// Create a new instance that is initially *empty* and that contains the
+    /// given failable *default* initializer.
+    ///
+    /// The "inner" value will be [initialized](Self::or_init_with()) on first
+    /// access. Default initialization *is* supported by this instance.
+    pub fn with_failable_default_init<U>(default_init: U) -> Self
+    where
+        T: Sized,
+        U: Fn() -> IoResult<T> + 'static,
+        Captured<L, U>: Send + Sync,
+    {
+        Self::with_default(DefaultInit::Failable(L::Threading::box_init(default_init, |init, _| init().map(P::new).map_err(InitError::Failed))))
+    }
+
+    /// Create a new instance that is initially *empty* and that contains the
+    /// given *default* initializer, which returns a pointer of type
+    /// `P::Pointer<T>`, or any other value that can be converted into one.
+    ///
+    /// Unlike [`with_default_init()`](Self::with_default_init), this function
+    /// also supports *unsized* types, such as `dyn Trait`, `str` or `[T]`.
+    pub fn with_default_init_pointer<U, V>(default_init: U) -> Self
+    where
+        U: Fn() -> V + 'static,
+        V: Into<P::Pointer<T>>,
+        Captured<L, U>: Send + Sync,
+    {
+        Self::with_default(DefaultInit::Infailable(L::Threading::box_init(default_init, |init, _| Ok(init().into()))))
+    }
+
+    /// Create a new instance that is initially *empty* and that contains the
+    /// given failable *default* initializer, which returns a pointer of type
+    /// `P::Pointer<T>`, or any other value that can be converted into one.
+    ///
+    /// Unlike [`with_failable_default_init()`](Self::with_failable_default_init),
+    /// this function also supports *unsized* types, such as `dyn Trait`.
+    pub fn with_failable_default_init_pointer<U, V>(default_init: U) -> Self
+    where
+        U: Fn() -> IoResult<V> + 'static,
+        V: Into<P::Pointer<T>>,
+        Captured<L, U>: Send + Sync,
+    {
+        Self::with_default(DefaultInit::Failable(L::Threading::box_init(default_init, |init, _| init().map(Into::into).map_err(InitError::Failed))))
+    }
+
+    /// Create a new instance that is initially *empty* and that contains the
+    /// given failable *cancellable* default initializer.
+    ///
+    /// This works like [`with_failable_default_init()`](Self::with_failable_default_init),
+    /// except that the default initializer receives a [`CancellationToken`],
+    /// which is cancelled when the caller of
+    /// [`or_try_init_timeout()`](Self::or_try_init_timeout) gives up. All
+    /// other initialization functions pass a token that is **never** cancelled.
+    pub fn with_cancellable_default_init<U>(default_init: U) -> Self
+    where
+        T: Sized,
+        U: Fn(&CancellationToken) -> IoResult<T> + 'static,
+        Captured<L, U>: Send + Sync,
+    {
+        Self::with_default(DefaultInit::Cancellable(L::Threading::box_init(default_init, |init, token| init(token).map(P::new).map_err(InitError::Failed))))
+    }
+
+    /// Create a new instance that is initially *empty* and whose *default*
+    /// initializer parses the value of the environment variable `name`.
+    ///
+    /// On first access, the environment variable is read and its value is
+    /// passed to `parse_fn()`, which creates the "inner" value. If the
+    /// environment variable is **not** set, or if `parse_fn()` fails, then
+    /// [`or_try_init()`](Self::or_try_init) returns an error of type
+    /// [`InitError::Env`], which contains the name of the variable.
+    pub fn from_env<N, E, F>(name: N, parse_fn: F) -> Self
+    where
+        T: Sized,
+        N: Into<String>,
+        E: Into<BoxError>,
+        F: Fn(&str) -> Result<T, E> + Send + Sync + 'static,
+    {
+        Self::with_init_error_default_init(sources::env_var(name.into(), parse_fn))
+    }
+
+    /// Create a new instance that is initially *empty* and whose *default*
+    /// initializer parses the content of the file at `path`.
+    ///
+    /// On first access, the file is read as UTF-8 text and its content is
+    /// passed to `parse_fn()`, which creates the "inner" value. If the file
+    /// can **not** be read, or if `parse_fn()` fails, then
+    /// [`or_try_init()`](Self::or_try_init) returns an error of type
+    /// [`InitError::File`], which contains the path of the file.
+    pub fn from_file<Q, E, F>(path: Q, parse_fn: F) -> Self
+    where
+        T: Sized,
+        Q: Into<PathBuf>,
+        E: Into<BoxError>,
+        F: Fn(&str) -> Result<T, E> + Send + Sync + 'static,
+    {
+        Self::with_init_error_default_init(sources::file(path.into(), parse_fn))
+    }
+
+    /// Create a new instance that is initially *empty* and whose *default*
+    /// initializer deserializes the JSON file at `path`.
+    ///
+    /// This works like [`from_file()`](Self::from_file), except that the
+    /// content of the file is deserialized into `T` via `serde_json`.
+    ///
+    /// This function is only available with the `json` feature enabled.
+    #[cfg(feature = "json")]
+    pub fn from_json_file<Q>(path: Q) -> Self
+    where
+        T: Sized + serde::de::DeserializeOwned,
+        Q: Into<PathBuf>,
+    {
+        Self::with_init_error_default_init(sources::json_file(path.into()))
+    }
+
+    /// Create a new instance that is initially *empty* and whose *default*
+    /// initializer deserializes the TOML file at `path`.
+    ///
+    /// This works like [`from_file()`](Self::from_file), except that the
+    /// content of the file is deserialized into `T` via `toml`.
+    ///
+    /// This function is only available with the `toml` feature enabled.
+    #[cfg(feature = "toml")]
+    pub fn from_toml_file<Q>(path: Q) -> Self
+    where
+        T: Sized + serde::de::DeserializeOwned,
+        Q: Into<PathBuf>,
+    {
+        Self::with_init_error_default_init(sources::toml_file(path.into()))
+    }
+
+    /// Create a new instance that is initially *empty* and whose *default*
+    /// initializer is backed by the given persistent on-disk `cache`.
+    ///
+    /// On first access, the "inner" value is loaded from the cache file, if a
+    /// *valid* cache file exists. Otherwise, `default_init()` is called and, if
+    /// it succeeds, its result is stored in the cache file for the next start.
+    /// See [`WarmCache::load_or_init()`](crate::WarmCache::load_or_init) for
+    /// details.
+    ///
+    /// This function is only available with the `persist` feature enabled.
+    #[cfg(feature = "persist")]
+    pub fn with_warm_cache<U>(cache: crate::WarmCache, default_init: U) -> Self
+    where
+        T: Sized + serde::Serialize + serde::de::DeserializeOwned,
+        U: Fn() -> IoResult<T> + Send + Sync + 'static,
+    {
+        Self::with_init_error_default_init(move || cache.load_or_init(&default_init).map_err(InitError::Failed))
+    }
+
+    /// Returns `true`, if and only if th "inner" value is initialized.
+    ///
+    /// This function never blocks on a pending initialization.
+    pub fn is_initialized(&self) -> bool {
+        #[cfg(feature = "test-util")]
+        if self.overrides.current().is_some() {
+            return true;
+        }
+        self.status.inspect(|status| status.is_initialized())
+    }
+
+    /// Returns the current [state](State) of this instance.
+    ///
+    /// Unlike [`is_initialized()`](Self::is_initialized), this distinguishes
+    /// an instance that is being initialized by some thread right now, and an
+    /// instance whose most recent initialization attempt has failed (or
+    /// panicked). This function never blocks on a pending initialization.
+    ///
+    /// Like `is_initialized()`, this honors an override for the current
+    /// thread (see `override_scoped_local()`), which is reported as
+    /// *initialized* at the time it was installed.
+    pub fn state(&self) -> State {
+        #[cfg(feature = "test-util")]
+        if let Some(at) = self.overrides.installed_at() {
+            return State::Initialized { at };
+        }
+        self.status.inspect(|status| status.state())
+    }
+
+    /// Returns a pointer to the existing "inner" value, or tries to initialize
+    /// the value right now.
+    ///
+    /// If and only if the "inner" value is **not** initialized yet, the
+    /// "inner" value is set to the return value of the *default* initializer
+    /// and a new pointer to the "inner" value is returned. The default
+    /// initializer **must** be *infailable*, otherwise use
+    /// [`or_try_init()`](Self::or_try_init)!
+    ///
+    /// Warning: This function [panics](mod@std::panic), if **no** *default*
+    /// initializer is available, or of the default initializer is *failable*!
+    pub fn or_init(&self) -> P::Pointer<T> {
+        match &self.default_init {
+            DefaultInit::Infailable(init) => self.init_with(|| call_infailable(init.as_ref())),
+            _ => panic!("No infailable default initializer!"),
+        }
+    }
+
+    /// Returns a pointer to the existing "inner" value, or tries to initialize
+    /// the value right now.
+    ///
+    /// If and only if the "inner" value is **not** initialized yet, the
+    /// "inner" value is set to the return value of the *default* initializer
+    /// and a new pointer to the "inner" value is returned. If the *default*
+    /// initializer fails, the error is passed through.
+    ///
+    /// If **no** *default* initializer is available, an error of type
+    /// [`NoDefaultInitializer`](crate::InitError) is returned.
+    pub fn or_try_init(&self) -> Result<P::Pointer<T>, InitError> {
+        match &self.default_init {
+            DefaultInit::Infailable(init) => Ok(self.init_with(|| call_infailable(init.as_ref()))),
+            DefaultInit::Failable(init) | DefaultInit::Cancellable(init) => self.try_init_described(|| init(&CancellationToken::new()), InitError::to_string),
+            DefaultInit::None => Err(InitError::NoDefaultInitializer)
+        }
+    }
+
+    /// Returns a pointer to the existing "inner" value, or tries to initialize
+    /// the value right now, giving up after `timeout`.
+    ///
+    /// This works like [`or_try_init()`](Self::or_try_init), except that a
+    /// caller that has to *wait* for another thread, which is initializing the
+    /// "inner" value right now, gives up once `timeout` has elapsed, returning
+    /// an error of type [`Timeout`](crate::InitError). The pending
+    /// initialization is **not** affected by that; if it succeeds later, the
+    /// "inner" value is set as usual. A `timeout` that is too large to be
+    /// represented as a deadline, e.g. `Duration::MAX`, means **no** timeout.
+    ///
+    /// If the calling thread runs the *default* initializer itself, that
+    /// initializer can **not** be interrupted. However, a *cancellable*
+    /// default initializer (see
+    /// [`with_cancellable_default_init()`](Self::with_cancellable_default_init))
+    /// receives a [`CancellationToken`] that is cancelled at the deadline, so
+    /// that it can abort cooperatively. If it fails *after* the deadline, an
+    /// error of type `Timeout` is returned.
+    ///
+    /// Note: The timeout only applies to waiting for a pending initialization.
+    /// Another thread that holds *shared* access for a long time, e.g. inside
+    /// of [`map()`](Self::map), still delays the caller beyond the deadline.
+    pub fn or_try_init_timeout(&self, timeout: Duration) -> Result<P::Pointer<T>, InitError> {
+        if matches!(self.default_init, DefaultInit::None) {
+            return Err(InitError::NoDefaultInitializer);
+        }
+        let deadline = Instant::now().checked_add(timeout);
+        let token = CancellationToken::with_deadline(deadline);
+        self.access();
+        #[cfg(feature = "test-util")]
+        if let Some(value) = self.overrides.current() {
+            return Ok(value);
+        }
+        // A pending initialization holds the lock, so it must not be blocked on
+        if let Some(value) = L::try_read(&self.inner).and_then(|inner| inner.clone()) {
+            return Ok(value);
+        }
+        let _claim = self.status.claim(deadline).ok_or(InitError::Timeout)?;
+        match self.lock_for_init() {
+            Ok(inner) => self.try_init_locked(inner, || self.default_init.call(&token), InitError::to_string),
+            Err(value) => Ok(value),
+        }
+    }
+
+    /// Returns a pointer to the existing "inner" value, or initializes the
+    /// value right now.
+    ///
+    /// If and only if the "inner" value is **not** initialized yet, the
+    /// function `init_fn()` is called to create the value. The "inner" value
+    /// is then set to the return value of `init_fn()` and a new pointer to
+    /// the "inner" value is returned.
+    pub fn or_init_with<F>(&self, init_fn: F) -> P::Pointer<T>
+    where
+        T: Sized,
+        F: FnOnce() -> T
+    {
+        self.init_with(|| P::new(init_fn()))
+    }
+
+    /// Returns a pointer to the existing "inner" value, or tries to
+    /// initializes the value right now.
+    ///
+    /// If and only if the "inner" value is **not** initialized yet, the
+    /// function `init_fn()` is called to create the value. In case that
+    /// `init_fn()` returns an error, that error is passed through and the
+    /// "inner" value remains in the *uninitialized* state for now. If the
+    /// "inner" value already existed or if it was created successfully just
+    /// now, a new pointer to the "inner" value is returned.
+    pub fn or_try_init_with<E, F>(&self, init_fn: F) -> Result<P::Pointer<T>, E>
+    where
+        T: Sized,
+        F: FnOnce() -> Result<T, E>
+    {
+        self.try_init_with(|| init_fn().map(P::new))
+    }
+
+    /// Returns a pointer to the existing "inner" value, or initializes the
+    /// value right now, using an initializer that returns a pointer.
+    ///
+    /// This works like [`or_init_with()`](Self::or_init_with), except that
+    /// `init_fn()` returns a pointer of type `P::Pointer<T>`, or any other
+    /// value that can be converted into one (e.g. `Box<T>`, `String` or
+    /// `Vec<U>`). Therefore, this function also supports *unsized* types, such
+    /// as `dyn Trait`, `str` or `[U]`.
+    pub fn or_init_with_pointer<V, F>(&self, init_fn: F) -> P::Pointer<T>
+    where
+        V: Into<P::Pointer<T>>,
+        F: FnOnce() -> V
+    {
+        self.init_with(|| init_fn().into())
+    }
+
+    /// Returns a pointer to the existing "inner" value, or tries to
+    /// initializes the value right now, using an initializer that returns a
+    /// pointer.
+    ///
+    /// This works like [`or_try_init_with()`](Self::or_try_init_with), except
+    /// that `init_fn()` returns a pointer of type `P::Pointer<T>`, or any other
+    /// value that can be converted into one (e.g. `Box<T>` or `String`).
+    /// Therefore, this function also supports *unsized* types, such as
+    /// `dyn Trait`, `str` or `[U]`.
+    pub fn or_try_init_with_pointer<V, E, F>(&self, init_fn: F) -> Result<P::Pointer<T>, E>
+    where
+        V: Into<P::Pointer<T>>,
+        F: FnOnce() -> Result<V, E>
+    {
+        self.try_init_with(|| init_fn().map(Into::into))
+    }
+
+    /// Returns a pointer to the existing "inner" value, or initializes the
+    /// value right now, giving the initializer a weak pointer to the new value.
+    ///
+    /// If and only if the "inner" value is **not** initialized yet, the value
+    /// is created via [`CyclicFamily::new_cyclic()`], e.g.
+    /// [`Arc::new_cyclic()`](std::sync::Arc::new_cyclic), i.e. `init_fn()` is
+    /// called with a weak pointer to the value that is being constructed. This
+    /// allows the value to hold a reference to itself, e.g. to register
+    /// callbacks.
+    ///
+    /// Note: Calling `upgrade()` on the weak pointer returns `None` until
+    /// `init_fn()` has returned.
+    pub fn or_init_cyclic<F>(&self, init_fn: F) -> P::Pointer<T>
+    where
+        T: Sized,
+        P: CyclicFamily,
+        F: FnOnce(&P::Weak<T>) -> T
+    {
+        self.init_with(|| P::new_cyclic(init_fn))
+    }
+
+    /// Returns a pointer to the existing "inner" value, or tries to
+    /// initializes the value right now, giving the initializer a weak pointer
+    /// to the new value.
+    ///
+    /// This works like [`or_init_cyclic()`](Self::or_init_cyclic), except
+    /// that `init_fn()` may fail. In case that `init_fn()` returns an error,
+    /// that error is passed through and the "inner" value remains in the
+    /// *uninitialized* state for now.
+    ///
+    /// Note: This function is only available with `panic = "unwind"`, because
+    /// the error is passed out of [`CyclicFamily::new_cyclic()`] by unwinding.
+    #[cfg(panic = "unwind")]
+    pub fn or_try_init_cyclic<E, F>(&self, init_fn: F) -> Result<P::Pointer<T>, E>
+    where
+        T: Sized,
+        P: CyclicFamily,
+        F: FnOnce(&P::Weak<T>) -> Result<T, E>
+    {
+        self.try_init_with(|| try_new_cyclic(|init| P::new_cyclic(init), init_fn))
+    }
+
+    /// An alias for the [`or_init()`](Self::or_init) function.
+    pub fn unwrap(&self) -> P::Pointer<T> {
+        self.or_init()
+    }
+
+    /// Applies function `map_fn()` to the "inner", if already initialized.
+    ///
+    /// If and only if the "inner" value already *is* initialize, the function
+    /// `map_fn()` is called with a reference to the "inner" value and its
+    /// return value is passed through. Otherwise the function `map_fn()` is
+    /// **not** called and `None` is returned.
+    pub fn map<U, F>(&self, map_fn: F) -> Option<U>
+    where
+        F: FnOnce(&P::Pointer<T>) -> U
+    {
+        self.access();
+        #[cfg(feature = "test-util")]
+        if let Some(value) = self.overrides.current() {
+            return Some(map_fn(&value));
+        }
+        self.read().as_ref().map(map_fn)
+    }
+
+    /// Returns a pointer to the "inner" value, if already initialized.
+    ///
+    /// If and only if the "inner" value already *is* initialized, the function
+    /// returns a new pointer to the "inner" value. Otherwise, if the "inner"
+    /// value is **not** initialized yet, the value remains in the
+    /// *uninitialized* state and the function returns `None`.
+    pub fn value(&self) -> Option<P::Pointer<T>> {
+        self.access();
+        self.current()
+    }
+
+    /// Takes the "inner" value out of this instance, if already initialized.
+    ///
+    /// If and only if the "inner" value already *is* initialized, the function
+    /// returns the pointer to the current "inner" value and resets this
+    /// instance' "inner" value to the *uninitialized* state. Otherwise, the
+    /// function simply returns `None`.
+    pub fn take(&mut self) -> Option<P::Pointer<T>> {
+        self.reset()
+    }
+
+    /// Replaces the "inner" value with `value`.
+    ///
+    /// The "inner" value is replaced, regardless of whether it already was
+    /// initialized or not, and the [generation](Self::generation) is advanced.
+    /// The pointer to the previous "inner" value is returned, if it was
+    /// initialized. Existing pointers to the previous "inner" value remain
+    /// valid.
+    pub fn replace<V>(&self, value: V) -> Option<P::Pointer<T>>
+    where
+        V: Into<P::Pointer<T>>,
+    {
+        let mut inner = self.write();
+        inner.replace(self.advance(value.into()))
+    }
+
+    /// Resets the "inner" value to the *uninitialized* state.
+    ///
+    /// If and only if the "inner" value already *is* initialized, the function
+    /// returns the pointer to the current "inner" value, resets the "inner"
+    /// value to the *uninitialized* state and advances the
+    /// [generation](Self::generation). Otherwise, the function simply returns
+    /// `None`. The next access will initialize the "inner" value again.
+    pub fn reset(&self) -> Option<P::Pointer<T>> {
+        let mut inner = self.write();
+        let value = inner.take();
+        if value.is_some() {
+            self.counters.reset();
+            self.changed(None);
+        }
+        value
+    }
+
+    /// Returns the current *generation* of the "inner" value.
+    ///
+    /// The generation is a number that starts at zero for an *uninitialized*
+    /// instance and that is incremented every time the "inner" value is
+    /// initialized, replaced or reset. It can be used to detect whether a
+    /// previously obtained pointer is outdated.
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::Acquire)
+    }
+
+    /// Sets the *idle timeout* of this instance, or disables it (`None`).
+    ///
+    /// If the "inner" value has **not** been accessed for longer than the
+    /// idle timeout, it is *evicted*, i.e. this instance drops its pointer to
+    /// the "inner" value and returns to the *uninitialized* state, so that
+    /// the next access runs the initializer again. Pointers to the "inner"
+    /// value that exist elsewhere remain valid.
+    ///
+    /// The idle timeout is checked on every access, but an idle value can
+    /// also be evicted *without* an access, by calling
+    /// [`evict_if_idle()`](Self::evict_if_idle), e.g. from a sweeper thread,
+    /// see [`spawn_idle_sweeper()`](Self::spawn_idle_sweeper). Only an
+    /// instance that has a *default* initializer is evicted, because the
+    /// value could **not** be rebuilt otherwise.
+    ///
+    /// This function is only available with the `idle-timeout` feature enabled.
+    #[cfg(feature = "idle-timeout")]
+    pub fn set_idle_timeout(&self, timeout: Option<Duration>) {
+        self.idle.set_timeout(timeout);
+        self.idle.touch();
+    }
+
+    /// Returns the *idle timeout* of this instance, if enabled, see
+    /// [`set_idle_timeout()`](Self::set_idle_timeout).
+    ///
+    /// This function is only available with the `idle-timeout` feature enabled.
+    #[cfg(feature = "idle-timeout")]
+    pub fn idle_timeout(&self) -> Option<Duration> {
+        self.idle.timeout()
+    }
+
+    /// Evicts the "inner" value, if it has **not** been accessed for longer
+    /// than the *idle timeout*. Returns `true`, if the value was evicted.
+    ///
+    /// This function does **not** count as an access. If **no** idle timeout
+    /// is set, the function has **no** effect.
+    ///
+    /// This function is only available with the `idle-timeout` feature enabled.
+    #[cfg(feature = "idle-timeout")]
+    pub fn evict_if_idle(&self) -> bool {
+        if !self.idle.is_expired() {
+            return false;
+        }
+        let mut inner = self.write();
+        self.idle.is_expired() && self.evict(&mut inner)
+    }
+
+    /// Advances the clock of the *idle timeout* of this instance by
+    /// `duration`, as if that much time had passed since the most recent
+    /// access.
+    ///
+    /// This is intended for *testing* idle eviction without sleeping. Only
+    /// this instance is affected.
+    ///
+    /// This function is only available with the `idle-timeout` and the
+    /// `test-util` features enabled.
+    #[cfg(all(feature = "idle-timeout", feature = "test-util"))]
+    pub fn advance_idle_clock(&self, duration: Duration) {
+        self.idle.advance(duration);
+    }
+
+    /// Spawns a *sweeper* thread that calls
+    /// [`evict_if_idle()`](Self::evict_if_idle) every `interval`, so that an
+    /// idle "inner" value is evicted, even if this instance is **not**
+    /// accessed anymore.
+    ///
+    /// The sweeper thread exits, once the *idle timeout* is disabled.
+    ///
+    /// This function is only available with the `idle-timeout` feature enabled.
+    #[cfg(feature = "idle-timeout")]
+    pub fn spawn_idle_sweeper(&'static self, interval: Duration) -> JoinHandle<()>
+    where
+        Self: Sync,
+    {
+        thread::Builder::new()
+            .name(String::from("lazy_rc-idle-sweeper"))
+            .spawn(move || {
+                while self.idle.timeout().is_some() {
+                    self.evict_if_idle();
+                    thread::sleep(interval);
+                }
+            })
+            .expect("Failed to spawn the sweeper thread!")
+    }
+
+    /// Returns the statistics of this instance, i.e. the number of accesses,
+    /// initializations, failed attempts and resets, as well as the time spent
+    /// in initializers and waiting for the lock.
+    ///
+    /// This function is only available with the `stats` feature enabled.
+    #[cfg(feature = "stats")]
+    pub fn stats(&self) -> Stats {
+        self.counters.snapshot()
+    }
+
+    /// Registers this instance for [`shutdown()`](crate::shutdown), which
+    /// resets the "inner" value (if initialized) and thereby drops it, unless
+    /// it is still referenced elsewhere.
+    ///
+    /// Registered instances are shut down in *reverse* initialization order,
+    /// i.e. an instance that was (most recently) initialized *after* another
+    /// instance is shut down *before* that instance. Resetting this instance
+    /// may take at most `timeout`. Registering the same instance again only
+    /// updates the timeout.
+    ///
+    /// This function is only available with the `shutdown` feature enabled.
+    #[cfg(feature = "shutdown")]
+    pub fn register_shutdown(&'static self, timeout: Duration)
+    where
+        Self: Sync,
+    {
+        Registration::register(self, timeout)
+    }
+
+    /// Sets the behavior of this instance in the *child* process after a
+    /// `fork()`, see [`ForkPolicy`] for details.
+    ///
+    /// The policy is implemented via `pthread_atfork()` handlers, which lock
+    /// this instance while forking, i.e. a `fork()` waits for a pending
+    /// initialization by another thread to complete. Subscribers are **not**
+    /// notified about a reset in the child. The re-initialization of
+    /// [`ForkPolicy::ReinitializeInChild`] is deferred to the first access in
+    /// the child, so that the *default* initializer never runs inside of the
+    /// `fork()` call, while other instances may still be locked.
+    ///
+    /// This function is only available on Unix with the `fork` feature
+    /// enabled.
+    #[cfg(all(unix, feature = "fork"))]
+    pub fn set_fork_policy(&'static self, policy: ForkPolicy)
+    where
+        Self: Sync,
+        L: LockFamily<Threading = ThreadSafe>,
+    {
+        fork::register(self, policy)
+    }
+
+    /// Registers this instance with the memory `budget`, using `estimate_fn()`
+    /// to estimate the size of the "inner" value.
+    ///
+    /// The size is estimated whenever the "inner" value is initialized or
+    /// replaced. If the total estimated usage of the budget then exceeds its
+    /// limit, the *least recently used* instances are reset, see
+    /// [`MemoryBudget`] for details. An instance can only be registered with
+    /// a *single* budget; registering it again [panics](mod@std::panic).
+    ///
+    /// This function is only available with the `memory-budget` feature
+    /// enabled.
+    #[cfg(feature = "memory-budget")]
+    pub fn register_budget<F>(&'static self, budget: &'static MemoryBudget, estimate_fn: F)
+    where
+        Self: Sync,
+        F: Fn(&T) -> usize + Send + Sync + 'static,
+    {
+        if !self.budget.link(budget, estimate_fn) {
+            panic!("Already registered with a memory budget!");
+        }
+        budget.register(self);
+        let inner = self.read();
+        self.budget.resize(inner.as_deref());
+    }
+
+    /// Returns the estimated size of the "inner" value, if this instance is
+    /// registered with a memory budget, see
+    /// [`register_budget()`](Self::register_budget). The size is zero, while
+    /// the "inner" value is **not** initialized.
+    ///
+    /// This function is only available with the `memory-budget` feature
+    /// enabled.
+    #[cfg(feature = "memory-budget")]
+    pub fn budget_usage(&self) -> Option<usize> {
+        self.budget.budget().map(|_| self.budget.size())
+    }
+
+    /// Returns a pointer to the "inner" value together with its generation,
+    /// if already initialized.
+    ///
+    /// This works like [`value()`](Self::value), except that the current
+    /// [generation](Self::generation) is returned too. The pointer and the
+    /// generation are guaranteed to be consistent with each other.
+    pub fn value_with_generation(&self) -> Option<(P::Pointer<T>, u64)> {
+        #[cfg(feature = "test-util")]
+        if let Some(value) = self.overrides.current() {
+            return Some((value, self.generation()));
+        }
+        let inner = self.read();
+        inner.as_ref().map(|value| (value.clone(), self.generation()))
+    }
+
+    /// Returns a [`Handle`] to the "inner" value, if already initialized.
+    ///
+    /// The handle holds a pointer to the "inner" value, and it can cheaply
+    /// check whether that value still is *current*, i.e. whether the "inner"
+    /// value has **not** been replaced or reset in the meantime.
+    pub fn handle(&self) -> Option<Handle<'_, T, P, L>> {
+        self.value_with_generation().map(|(value, generation)| Handle { lazy: self, value, generation })
+    }
+
+    /// Subscribes to the changes of the "inner" value.
+    ///
+    /// The returned [`Subscription<T, P>`] is notified every time the "inner"
+    /// value is initialized, [replaced](Self::replace) or
+    /// [reset](Self::reset). If multiple changes happen before the subscriber
+    /// receives the notification, only the *latest* change is kept. The
+    /// "inner" value at the time of subscribing is available via
+    /// [`Subscription::initial()`]; **no** change can be missed between that
+    /// value and the first notification.
+    ///
+    /// Note: Changes made via [`make_mut()`](Self::make_mut) are **not**
+    /// published, because they require *exclusive* access to the value.
+    pub fn subscribe(&self) -> Subscription<T, P> {
+        let inner = self.read();
+        self.subscribers.subscribe(inner.clone())
+    }
+
+    /// Returns a future that resolves to a pointer to the "inner" value, once
+    /// it has been initialized.
+    ///
+    /// The returned future does **not** initialize the "inner" value itself;
+    /// it waits until another task or thread has initialized the value, in
+    /// any possible way. If the "inner" value already *is* initialized, the
+    /// future resolves immediately. If the "inner" value has been reset again
+    /// by the time the future is polled, then the future keeps waiting for the
+    /// next initialization.
+    pub fn initialized(&self) -> Initialized<'_, T, P, L> {
+        Initialized { lazy: self, subscription: None }
+    }
+
+    /// Temporarily overrides the "inner" value with `value`, until the
+    /// returned [`OverrideGuard`] is dropped.
+    ///
+    /// This is intended for *testing* code that accesses a global `static`
+    /// instance. The override is visible to **all** threads. Once the guard
+    /// is dropped, the previous state is restored, including the
+    /// *uninitialized* state. Overrides may be nested, but the guards must be
+    /// dropped in reverse order of creation.
+    ///
+    /// This function is only available with the `test-util` feature enabled.
+    #[cfg(feature = "test-util")]
+    pub fn override_scoped<V>(&self, value: V) -> OverrideGuard<'_, T, P, L>
+    where
+        V: Into<P::Pointer<T>>,
+    {
+        let previous = self.replace(value);
+        OverrideGuard { lazy: self, scope: OverrideScope::Global(previous) }
+    }
+
+    /// Temporarily overrides the "inner" value with `value` for the *current*
+    /// thread only, until the returned [`OverrideGuard`] is dropped.
+    ///
+    /// This works like [`override_scoped()`](Self::override_scoped), except
+    /// that the override is **only** visible to the current thread, so that
+    /// tests running in parallel do **not** interfere with each other. The
+    /// actual "inner" value is left untouched.
+    ///
+    /// This function is only available with the `test-util` feature enabled.
+    #[cfg(feature = "test-util")]
+    pub fn override_scoped_local<V>(&self, value: V) -> OverrideGuard<'_, T, P, L>
+    where
+        V: Into<P::Pointer<T>>,
+    {
+        let value = value.into();
+        self.overrides.push(value.clone());
+        self.generation.fetch_add(1, Ordering::AcqRel);
+        OverrideGuard { lazy: self, scope: OverrideScope::Local(value) }
+    }
+
+    /// Takes the "inner" value out of this instance, if already initialized
+    /// *and* if this instance is the *only* owner of the value.
+    ///
+    /// If and only if the "inner" value already *is* initialized and there
+    /// are **no** other pointers to the "inner" value, the function returns
+    /// the "inner" value itself. Otherwise, the function returns `None`; in
+    /// this case the "inner" value (if any) is **not** dropped, as long as
+    /// other pointers to the value exist.
+    pub fn into_inner(mut self) -> Option<T>
+    where
+        T: Sized,
+    {
+        self.take().and_then(|value| P::try_unwrap(value).ok())
+    }
+
+    /// Returns a mutable reference to the "inner" value, if already
+    /// initialized *and* if this instance is the *only* owner of the value.
+    ///
+    /// If and only if the "inner" value already *is* initialized and there
+    /// are **no** other (strong or weak) pointers to the "inner" value, the
+    /// function returns a mutable reference to the "inner" value. Otherwise,
+    /// the function returns `None`.
+    pub fn get_mut(&mut self) -> Option<&mut T> {
+        L::get_mut(&mut self.inner).as_mut().and_then(P::get_mut)
+    }
+
+    /// Returns a mutable reference to the "inner" value, initializing and/or
+    /// cloning the value as needed ("copy-on-write").
+    ///
+    /// If the "inner" value is **not** initialized yet, it is initialized
+    /// with the *default* initializer first. If there are other pointers to
+    /// the "inner" value, then the "inner" value is *cloned*, and this
+    /// instance is updated to point to the clone. See
+    /// [`Arc::make_mut()`](std::sync::Arc::make_mut) for details!
+    ///
+    /// Warning: This function [panics](mod@std::panic), if **no** *default*
+    /// initializer is available, or of the default initializer is *failable*!
+    pub fn make_mut(&mut self) -> &mut T
+    where
+        T: Clone,
+    {
+        if L::get_mut(&mut self.inner).is_none() {
+            // The returned pointer is dropped right away, so no clone is forced
+            self.or_init();
+        }
+        self.detach();
+        P::make_mut(L::get_mut(&mut self.inner).as_mut().unwrap())
+    }
+
+    /// Returns a mutable reference to the "inner" value, initializing and/or
+    /// cloning the value as needed ("copy-on-write").
+    ///
+    /// This works like [`make_mut()`](Self::make_mut), except that the
+    /// *default* initializer may fail. If the *default* initializer fails,
+    /// the error is passed through. If **no** *default* initializer is
+    /// available, an error of type [`NoDefaultInitializer`](crate::InitError)
+    /// is returned.
+    pub fn try_make_mut(&mut self) -> Result<&mut T, InitError>
+    where
+        T: Clone,
+    {
+        if L::get_mut(&mut self.inner).is_none() {
+            // The returned pointer is dropped right away, so no clone is forced
+            self.or_try_init()?;
+        }
+        self.detach();
+        Ok(P::make_mut(L::get_mut(&mut self.inner).as_mut().unwrap()))
+    }
+
+    /// Create a new instance that is already initialized to `value`.
+    pub(crate) fn with_value(value: P::Pointer<T>) -> Self {
+        let mut lazy = Self::from_parts(L::new(Some(value)), DefaultInit::None, L::Threading::new_status(), Subscribers::new(L::Threading::new_cell(Vec::new())));
+        *lazy.generation.get_mut() = 1;
+        lazy.status.with(|status| status.changed(true));
+        #[cfg(feature = "shutdown")]
+        lazy.registration.initialized();
+        lazy
+    }
+
+    /// Consumes this instance, returning the pointer to the "inner" value, if
+    /// already initialized.
+    pub(crate) fn into_pointer(self) -> Option<P::Pointer<T>> {
+        L::into_inner(self.inner)
+    }
+
+    fn with_init_error_default_init<U>(default_init: U) -> Self
+    where
+        T: Sized,
+        U: Fn() -> Result<T, InitError> + Send + Sync + 'static,
+    {
+        Self::with_default(DefaultInit::Failable(L::Threading::box_sync_init(move |_| default_init().map(P::new))))
+    }
+
+    fn with_default(default_init: DefaultInit<InitFn<T, P, L>>) -> Self {
+        Self::from_parts(L::new(None), default_init, L::Threading::new_status(), Subscribers::new(L::Threading::new_cell(Vec::new())))
+    }
+
+    const fn from_parts(
+        inner: L::Lock<Option<P::Pointer<T>>>,
+        default_init: DefaultInit<InitFn<T, P, L>>,
+        status: StatusOf<L>,
+        subscribers: Subscribers<T, P, L::Threading>,
+    ) -> Self {
+        Self {
+            inner,
+            default_init,
+            generation: AtomicU64::new(0),
+            subscribers,
+            counters: Counters::new(),
+            status,
+            #[cfg(feature = "idle-timeout")]
+            idle: IdleTimer::new(),
+            #[cfg(feature = "memory-budget")]
+            budget: BudgetLink::new(),
+            #[cfg(feature = "test-util")]
+            overrides: Overrides::new(),
+            #[cfg(feature = "shutdown")]
+            registration: Registration::new(),
+            #[cfg(all(unix, feature = "fork"))]
+            reinitialize: AtomicBool::new(false),
+        }
+    }
+
+    /// Returns a pointer to the existing "inner" value, or initializes the
+    /// value right now, using `init_fn()`.
+    fn init_with<F>(&self, init_fn: F) -> P::Pointer<T>
+    where
+        F: FnOnce() -> P::Pointer<T>
+    {
+        self.access();
+        if let Some(value) = self.current() {
+            return value;
+        }
+        let _claim = self.status.claim(None);
+        match self.lock_for_init() {
+            Ok(inner) => or_init_with(inner, || {
+                self.advance(self.counters.init(|| track_infailable(&self.status, init_fn)))
+            }),
+            Err(value) => value,
+        }
+    }
+
+    /// Returns a pointer to the existing "inner" value, or tries to
+    /// initializes the value right now, using `init_fn()`.
+    fn try_init_with<E, F>(&self, init_fn: F) -> Result<P::Pointer<T>, E>
+    where
+        F: FnOnce() -> Result<P::Pointer<T>, E>
+    {
+        self.try_init_described(init_fn, describe::<E>)
+    }
+
+    /// Works like [`try_init_with()`](Self::try_init_with), but a failure is
+    /// described by `describe_fn()`.
+    fn try_init_described<E, F, D>(&self, init_fn: F, describe_fn: D) -> Result<P::Pointer<T>, E>
+    where
+        F: FnOnce() -> Result<P::Pointer<T>, E>,
+        D: FnOnce(&E) -> String,
+    {
+        self.access();
+        if let Some(value) = self.current() {
+            return Ok(value);
+        }
+        let _claim = self.status.claim(None);
+        match self.lock_for_init() {
+            Ok(inner) => self.try_init_locked(inner, init_fn, describe_fn),
+            Err(value) => Ok(value),
+        }
+    }
+
+    /// Tries to initialize the "inner" value, if still *uninitialized*, while
+    /// the "inner" value is locked by `inner`.
+    fn try_init_locked<E, F, D>(&self, inner: InnerGuard<'_, T, P, L>, init_fn: F, describe_fn: D) -> Result<P::Pointer<T>, E>
+    where
+        F: FnOnce() -> Result<P::Pointer<T>, E>,
+        D: FnOnce(&E) -> String,
+    {
+        or_try_init_with(inner, || {
+            self.counters.try_init(|| track(&self.status, init_fn, describe_fn)).map(|value| self.advance(value))
+        })
+    }
+
+    /// Returns a pointer to the existing "inner" value, or tries to initialize
+    /// the value right now, by awaiting the future returned by `init_fn()`.
+    ///
+    /// The *initializing* state is claimed before `init_fn()` is called, so
+    /// that concurrent callers wait for the pending initialization, instead
+    /// of running their own initializer. The "inner" value is **not** locked
+    /// while the future is pending.
+    pub(crate) async fn try_init_async<E, F, U>(&self, init_fn: F) -> Result<P::Pointer<T>, E>
+    where
+        F: FnOnce() -> U,
+        U: Future<Output = Result<P::Pointer<T>, E>>,
+    {
+        self.access();
+        if let Some(value) = self.current() {
+            return Ok(value);
+        }
+        let tracking = self.status.claim_async().await;
+        if let Some(value) = self.current() {
+            return Ok(value);
+        }
+        match self.counters.try_init_async(init_fn()).await {
+            Ok(value) => {
+                let mut inner = self.write();
+                let value = inner.get_or_insert_with(|| self.advance(value)).clone();
+                drop(tracking);
+                Ok(value)
+            },
+            Err(error) => {
+                tracking.failed(describe(&error));
+                Err(error)
+            },
+        }
+    }
+
+    /// Counts an access and evicts the "inner" value, if it has been idle for
+    /// longer than the *idle timeout*. The first access in a *forked* child
+    /// process re-initializes the value, if requested by the fork policy.
+    fn access(&self) {
+        self.counters.access();
+        #[cfg(all(unix, feature = "fork"))]
+        if self.reinitialize.swap(false, Ordering::AcqRel) {
+            // A failure leaves the value uninitialized, as recorded in the state
+            let _ = self.or_try_init();
+        }
+        #[cfg(feature = "memory-budget")]
+        self.budget.touch();
+        #[cfg(feature = "idle-timeout")]
+        {
+            self.evict_if_idle();
+            self.idle.touch();
+        }
+    }
+
+    /// Evicts the "inner" value, while it is locked by `inner`. A value that
+    /// has **no** *default* initializer is never evicted.
+    #[cfg(any(feature = "idle-timeout", feature = "memory-budget"))]
+    fn evict(&self, inner: &mut InnerGuard<'_, T, P, L>) -> bool {
+        if matches!(self.default_init, DefaultInit::None) {
+            return false;
+        }
+        match inner.take() {
+            Some(_) => {
+                self.counters.reset();
+                self.changed(None);
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Returns a pointer to the "inner" value, if already initialized, taking
+    /// overrides into account, but without counting as an access.
+    fn current(&self) -> Option<P::Pointer<T>> {
+        #[cfg(feature = "test-util")]
+        if let Some(value) = self.overrides.current() {
+            return Some(value);
+        }
+        self.read().as_ref().cloned()
+    }
+
+    fn read(&self) -> L::ReadGuard<'_, Option<P::Pointer<T>>> {
+        self.counters.lock(|| L::read(&self.inner))
+    }
+
+    fn write(&self) -> InnerGuard<'_, T, P, L> {
+        self.counters.lock(|| L::write(&self.inner))
+    }
+
+    /// Acquires *exclusive* access for initializing the "inner" value, via an
+    /// *upgradable* read, if supported by the lock family. If the "inner"
+    /// value turns out to be initialized already, it is returned as `Err`.
+    ///
+    /// The initialization must have been *claimed* before, so that other
+    /// callers wait for the pending initialization on the status, rather than
+    /// on the lock, which then is only contended briefly.
+    fn lock_for_init(&self) -> Result<InnerGuard<'_, T, P, L>, P::Pointer<T>> {
+        let inner = self.counters.lock(|| L::upgradable_read(&self.inner));
+        match inner.as_ref() {
+            Some(value) => Err(value.clone()),
+            None => Ok(self.counters.lock(|| L::upgrade(inner))),
+        }
+    }
+
+    /// Restores the "inner" value after an override has ended.
+    #[cfg(feature = "test-util")]
+    fn restore(&self, value: Option<P::Pointer<T>>) {
+        let mut inner = self.write();
+        *inner = value;
+        self.changed(inner.as_ref());
+    }
+
+    /// Advances the generation and notifies all subscribers about the change,
+    /// must be called while the "inner" value is locked!
+    fn changed(&self, value: Option<&P::Pointer<T>>) {
+        self.status.with(|status| status.changed(value.is_some()));
+        self.mark(value.is_some());
+        #[cfg(feature = "memory-budget")]
+        self.budget.resize(value.map(|value| &**value));
+        self.subscribers.publish(value);
+    }
+
+    /// Advances the generation, but does **not** notify any subscribers.
+    fn mark(&self, initialized: bool) {
+        if initialized {
+            #[cfg(feature = "idle-timeout")]
+            self.idle.touch();
+            #[cfg(feature = "shutdown")]
+            self.registration.initialized();
+        }
+        self.generation.fetch_add(1, Ordering::AcqRel);
+    }
+
+    /// Marks `value` as the new "inner" value, see [`changed()`](Self::changed).
+    fn advance(&self, value: P::Pointer<T>) -> P::Pointer<T> {
+        self.changed(Some(&value));
+        value
+    }
+
+    /// Advances the generation, if `make_mut()` is going to clone the value.
+    fn detach(&mut self) {
+        if L::get_mut(&mut self.inner).as_mut().is_some_and(|value| P::get_mut(value).is_none()) {
+            self.generation.fetch_add(1, Ordering::AcqRel);
+        }
+    }
+}
+
+#[cfg(feature = "shutdown")]
+impl<T: ?Sized, P: PointerFamily, L: LockFamily> Shutdown for Lazy<T, P, L>
+where
+    Self: Sync,
+{
+    fn registration(&self) -> &Registration {
+        &self.registration
+    }
+
+    fn type_name(&self) -> &'static str {
+        std::any::type_name::<T>()
+    }
+
+    fn shutdown(&self) -> Option<ShutdownOutcome> {
+        // Weak pointers, e.g. from `or_init_cyclic()`, do not keep the value alive
+        let value = self.reset()?;
+        match P::strong_count(&value) {
+            1 => Some(ShutdownOutcome::Dropped),
+            _ => Some(ShutdownOutcome::StillReferenced),
+        }
+    }
+}
+
+#[cfg(all(unix, feature = "fork"))]
+impl<T: ?Sized, P: PointerFamily, L: LockFamily<Threading = ThreadSafe>> Fork for Lazy<T, P, L>
+where
+    Self: Sync,
+{
+    fn try_lock(&'static self) -> Attempt {
+        let Some(status) = self.status.try_lock() else {
+            return Attempt::Busy;
+        };
+        if status.is_initializing_here() {
+            return Attempt::Skipped;
+        }
+        match L::try_write(&self.inner) {
+            Some(inner) => Attempt::Locked(Box::new(ForkLock { lazy: self, inner, status })),
+            None => Attempt::Busy,
+        }
+    }
+
+    fn wait(&self) {
+        // A pending initialization holds the claim, until it has completed
+        drop(self.status.wait(None));
+        // Any other thread holds the lock only briefly, or while inside of `map()`
+        drop(L::write(&self.inner));
+    }
+}
+
+/// A [`Lazy<T, P, L>`] that is locked while forking, see [`Fork`].
+#[cfg(all(unix, feature = "fork"))]
+struct ForkLock<T: ?Sized + 'static, P: PointerFamily + 'static, L: LockFamily<Threading = ThreadSafe> + 'static> {
+    lazy: &'static Lazy<T, P, L>,
+    inner: InnerGuard<'static, T, P, L>,
+    status: std::sync::MutexGuard<'static, Status>,
+}
+
+#[cfg(all(unix, feature = "fork"))]
+impl<T: ?Sized, P: PointerFamily, L: LockFamily<Threading = ThreadSafe>> Locked for ForkLock<T, P, L> {
+    fn child(self: Box<Self>, policy: ForkPolicy) {
+        let ForkLock { lazy, mut inner, mut status } = *self;
+        if policy == ForkPolicy::Keep {
+            return;
+        }
+        if let Some(value) = inner.take() {
+            std::mem::forget(value);
+            lazy.counters.reset();
+            lazy.mark(false);
+        }
+        status.forked();
+        if policy == ForkPolicy::ReinitializeInChild {
+            lazy.reinitialize.store(true, Ordering::Release);
+        }
+    }
+}
+
+#[cfg(feature = "memory-budget")]
+impl<T: ?Sized, P: PointerFamily, L: LockFamily> Budgeted for Lazy<T, P, L>
+where
+    Self: Sync,
+{
+    fn usage(&self) -> (usize, u64) {
+        (self.budget.size(), self.budget.last_access())
+    }
+
+    fn type_name(&self) -> &'static str {
+        std::any::type_name::<T>()
+    }
+
+    fn is_evictable(&self) -> bool {
+        !matches!(self.default_init, DefaultInit::None)
+    }
+
+    fn try_evict(&self) -> bool {
+        match self.counters.lock(|| L::try_write(&self.inner)) {
+            Some(mut inner) => self.evict(&mut inner),
+            None => false,
+        }
+    }
+}
+
+impl<T: ?Sized, P: PointerFamily, L: LockFamily> Default for Lazy<T, P, L> {
+    /// The default value is a new ***empty*** instance.
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, P: PointerFamily, L: LockFamily> From<T> for Lazy<T, P, L> {
+    /// Create a new instance that is already initialized to `value`.
+    fn from(value: T) -> Self {
+        Self::with_value(P::new(value))
+    }
+}
+
+impl<T, P: PointerFamily, L: LockFamily> From<&T> for Lazy<T, P, L>
+where
+    T: Clone,
+{
+    /// Create a new instance that is already initialized to `value`.
+    fn from(value: &T) -> Self {
+        Self::with_value(P::new(value.clone()))
+    }
+}
+
+impl<T: ?Sized, P: PointerFamily, L: LockFamily> From<Box<T>> for Lazy<T, P, L>
+where
+    P::Pointer<T>: From<Box<T>>,
+{
+    /// Create a new instance that is already initialized to `value`.
+    fn from(value: Box<T>) -> Self {
+        Self::with_value(value.into())
+    }
+}
+
+impl<T: ?Sized, P: PointerFamily, L: LockFamily> Clone for Lazy<T, P, L> {
+    /// Creates a clone of this instance.
+    ///
+    /// If the "inner" value of this instance *is* already initialized, the
+    /// clone will be pointing to the same "inner" value, i.e. the "inner"
+    /// value is **not** cloned. Otherwise, the clone will initially be
+    /// *empty*; it can be initialized ***independently*** from this instance.
+    fn clone(&self) -> Self {
+        match self.read().as_ref() {
+            Some(existing) => Self::with_value(existing.clone()),
+            None => Self::new(),
+        }
+    }
+}
+
+impl<T: ?Sized, P: PointerFamily, L: LockFamily> Debug for Lazy<T, P, L> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {{ default_init: {:?}, state: {:?} }}",
+            P::LAZY_NAME,
+            self.default_init,
+            self.state())
+    }
+}
+
+/// A pointer to the "inner" value of a [`Lazy<T, P, L>`], which remembers the
+/// [generation](`Lazy::generation`) of the value.
+///
+/// A `Handle` can cheaply check whether the value it points to still is the
+/// *current* "inner" value of the originating instance, and it can be
+/// [refreshed](Self::refresh) to point to the current "inner" value.
+pub struct Handle<'a, T: ?Sized, P: PointerFamily, L: LockFamily> {
+    lazy: &'a Lazy<T, P, L>,
+    value: P::Pointer<T>,
+    generation: u64,
+}
+
+impl<T: ?Sized, P: PointerFamily, L: LockFamily> Handle<'_, T, P, L> {
+    /// Returns `true`, if and only if the "inner" value of the originating
+    /// instance has **not** been replaced or reset since this handle was
+    /// created (or last refreshed).
+    pub fn is_current(&self) -> bool {
+        self.lazy.generation() == self.generation
+    }
+
+    /// Updates this handle to point to the current "inner" value of the
+    /// originating instance, if the value has changed.
+    ///
+    /// Returns `true`, if this handle was updated. If the originating
+    /// instance currently is *uninitialized*, this handle keeps pointing to
+    /// the outdated value and the function returns `false`.
+    pub fn refresh(&mut self) -> bool {
+        if self.is_current() {
+            return false;
+        }
+        match self.lazy.value_with_generation() {
+            Some((value, generation)) => {
+                self.value = value;
+                self.generation = generation;
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Returns the generation of the value that this handle points to.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Returns the pointer to the value that this handle points to.
+    pub fn value(&self) -> &P::Pointer<T> {
+        &self.value
+    }
+
+    /// Converts this handle into the pointer it holds.
+    pub fn into_inner(self) -> P::Pointer<T> {
+        self.value
+    }
+}
+
+impl<T: ?Sized, P: PointerFamily, L: LockFamily> Deref for Handle<'_, T, P, L> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T: ?Sized, P: PointerFamily, L: LockFamily> Clone for Handle<'_, T, P, L> {
+    fn clone(&self) -> Self {
+        Self { lazy: self.lazy, value: self.value.clone(), generation: self.generation }
+    }
+}
+
+impl<T: ?Sized + Debug, P: PointerFamily, L: LockFamily> Debug for Handle<'_, T, P, L> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Handle")
+            .field("value", &&*self.value)
+            .field("generation", &self.generation)
+            .finish()
+    }
+}
+
+/// A future that resolves to a pointer to the "inner" value of a
+/// [`Lazy<T, P, L>`], once it has been initialized.
+///
+/// See [`Lazy::initialized()`] for details!
+pub struct Initialized<'a, T: ?Sized, P: PointerFamily = ArcFamily, L: LockFamily = ArcLockFamily> {
+    lazy: &'a Lazy<T, P, L>,
+    subscription: Option<Subscription<T, P>>,
+}
+
+// The future is never pinned structurally, so it is `Unpin` for any pointer
+impl<T: ?Sized, P: PointerFamily, L: LockFamily> Unpin for Initialized<'_, T, P, L> { }
+
+impl<T: ?Sized, P: PointerFamily, L: LockFamily> Future for Initialized<'_, T, P, L> {
+    type Output = P::Pointer<T>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<P::Pointer<T>> {
+        let this = &mut *self;
+        let subscription = match &this.subscription {
+            Some(subscription) => subscription,
+            None => {
+                let subscription = this.lazy.subscribe();
+                if let Some(value) = subscription.initial() {
+                    return Poll::Ready(value.clone());
+                }
+                this.subscription.insert(subscription)
+            },
+        };
+        loop {
+            match Pin::new(&mut subscription.recv_async()).poll(cx) {
+                Poll::Ready(Ok(Some(value))) => return Poll::Ready(value),
+                Poll::Ready(Ok(None)) => continue,
+                Poll::Ready(Err(_)) => unreachable!("Lazy dropped while borrowed!"),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+impl<T: ?Sized, P: PointerFamily, L: LockFamily> Debug for Initialized<'_, T, P, L> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Initialized {{ lazy: {:?} }}", self.lazy)
+    }
+}
+
+/// A guard that restores the previous state of a [`Lazy<T, P, L>`] on drop.
+///
+/// See [`Lazy::override_scoped()`] for details!
+#[cfg(feature = "test-util")]
+#[must_use = "the override is removed immediately, if the guard is not kept"]
+pub struct OverrideGuard<'a, T: ?Sized, P: PointerFamily = ArcFamily, L: LockFamily = ArcLockFamily> {
+    lazy: &'a Lazy<T, P, L>,
+    scope: OverrideScope<T, P>,
+}
+
+#[cfg(feature = "test-util")]
+enum OverrideScope<T: ?Sized, P: PointerFamily> {
+    Global(Option<P::Pointer<T>>),
+    Local(P::Pointer<T>),
+}
+
+#[cfg(feature = "test-util")]
+impl<T: ?Sized, P: PointerFamily, L: LockFamily> Drop for OverrideGuard<'_, T, P, L> {
+    fn drop(&mut self) {
+        match &mut self.scope {
+            OverrideScope::Global(previous) => self.lazy.restore(previous.take()),
+            OverrideScope::Local(value) => {
+                self.lazy.overrides.remove(value);
+                self.lazy.generation.fetch_add(1, Ordering::AcqRel);
+            },
+        }
+    }
+}
+
+#[cfg(feature = "test-util")]
+impl<T: ?Sized, P: PointerFamily, L: LockFamily> Debug for OverrideGuard<'_, T, P, L> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let local = matches!(self.scope, OverrideScope::Local(_));
+        write!(f, "OverrideGuard {{ lazy: {:?}, local: {:?} }}", self.lazy, local)
+    }
+}
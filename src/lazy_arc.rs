@@ -2,252 +2,158 @@
  * lazy_rc - Rc<T> and Arc<T> with *lazy* initialization
  * This is free and unencumbered software released into the public domain.
  */
-use std::fmt::Debug;
 use std::io::{Result as IoResult};
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, LazyLock, OnceLock};
 
-use crate::InitError;
-use crate::utils::{DefaultInit, or_init_with, or_try_init_with};
+use crate::{Handle, Lazy};
+use crate::family::{ArcFamily, Captured, LockFamily, ThreadSafe};
+#[cfg(not(feature = "parking_lot"))]
+pub(crate) use crate::family::RwLockFamily as ArcLockFamily;
+#[cfg(feature = "parking_lot")]
+pub(crate) use crate::family::ParkingLotFamily as ArcLockFamily;
 
 /// A thread-safe reference-counting pointer, akin to
 /// [`Arc<T>`](std::sync::Arc), but with ***lazy*** initialization
-pub struct LazyArc<T> {
-    inner: RwLock<Option<Arc<T>>>,
-    default_init: DefaultInit<T>,
-}
-
-impl<T> LazyArc<T> {
-    /// Create a new `LazyArc<T>` that is initially *empty* and that contains
-    /// **no** *default* initializer.
-    /// 
-    /// The "inner" value will be [initialized](Self::or_init_with()) on first
-    /// access. Default initialization is **not** supported by this instance!
-    pub const fn empty() -> Self {
-        Self {
-            inner: RwLock::new(None),
-            default_init: DefaultInit::None,
-        }
-    }
-
+///
+/// This is an alias for a [`Lazy<T, P, L>`] that uses `Arc<T>` pointers and
+/// a `RwLock`, i.e. concurrent readers do **not** block each other. By
+/// default, this is a [`std::sync::RwLock`]. With the `parking_lot` feature
+/// enabled, it is a `parking_lot::RwLock` instead, which behaves differently:
+///
+/// * It is **never** poisoned. If an initializer panics, the instance simply
+///   remains *uninitialized* and the next access tries again, whereas the
+///   `std` lock is poisoned, so that every later access panics too.
+/// * It is *task-fair*, i.e. a thread that is waiting to write (e.g. to
+///   initialize, [replace](Lazy::replace) or [reset](Lazy::reset) the value)
+///   blocks new readers, so that writers can **not** be starved by a steady
+///   stream of readers. As a consequence, a thread that already holds read
+///   access, e.g. inside of [`map()`](Lazy::map), must **not** access the
+///   same instance again, or it may deadlock.
+/// * The check-then-initialize path uses an *upgradable* read, so readers
+///   are **not** blocked while a thread merely checks whether the value
+///   needs to be initialized. Only one thread at a time can hold upgradable
+///   access, though.
+pub type LazyArc<T> = Lazy<T, ArcFamily, ArcLockFamily>;
+
+/// A pointer to the "inner" value of a [`LazyArc<T>`], which remembers the
+/// [generation](`Lazy::generation`) of the value, see [`Handle`].
+pub type ArcHandle<'a, T> = Handle<'a, T, ArcFamily, ArcLockFamily>;
+
+impl<T: ?Sized, L: LockFamily> Lazy<T, ArcFamily, L> {
     /// Create a new `LazyArc<T>` that is initially *empty* and that contains
-    /// the given *default* initializer.
-    /// 
-    /// The "inner" value will be [initialized](Self::or_init_with()) on first
-    /// access. Default initialization *is* supported by this instance.
-    pub fn with_default_init<U>(default_init: U) -> Self
+    /// the given *default* initializer, which returns a `Arc<T>` pointer, or
+    /// any other value that can be converted into a `Arc<T>` pointer.
+    ///
+    /// Unlike [`with_default_init()`](Self::with_default_init), this function
+    /// also supports *unsized* types, such as `dyn Trait`, `str` or `[T]`.
+    pub fn with_default_init_arc<U, P>(default_init: U) -> Self
     where
-        U: Fn() -> T + Sync + 'static,
+        U: Fn() -> P + 'static,
+        P: Into<Arc<T>>,
+        Captured<L, U>: Send + Sync,
     {
-        Self {
-            inner: RwLock::new(None),
-            default_init: DefaultInit::Infailable(Box::new(default_init)),
-        }
+        Self::with_default_init_pointer(default_init)
     }
 
     /// Create a new `LazyArc<T>` that is initially *empty* and that contains
-    /// the given failable *default* initializer.
-    /// 
-    /// The "inner" value will be [initialized](Self::or_init_with()) on first
-    /// access. Default initialization *is* supported by this instance.
-    pub fn with_failable_default_init<U>(default_init: U) -> Self
+    /// the given failable *default* initializer, which returns a `Arc<T>`
+    /// pointer, or any other value that can be converted into a `Arc<T>`.
+    ///
+    /// Unlike [`with_failable_default_init()`](Self::with_failable_default_init),
+    /// this function also supports *unsized* types, such as `dyn Trait`.
+    pub fn with_failable_default_init_arc<U, P>(default_init: U) -> Self
     where
-        U: Fn() -> IoResult<T> + Sync + 'static,
+        U: Fn() -> IoResult<P> + 'static,
+        P: Into<Arc<T>>,
+        Captured<L, U>: Send + Sync,
     {
-        Self {
-            inner: RwLock::new(None),
-            default_init: DefaultInit::Failable(Box::new(default_init)),
-        }
-    }
-
-    /// Returns `true`, if and only if th "inner" value is initialized.
-    pub fn is_initialized(&self) -> bool {
-        self.inner.read().map(|val| val.is_some()).unwrap_or(false)
-    }
-
-    /// Returns a pointer to the existing "inner" value, or tries to initialize
-    /// the value right now.
-    /// 
-    /// If and only if the "inner" value is **not** initialized yet, the
-    /// "inner" value is set to the return value of the *default* initializer
-    /// and a new `Arc<T>` pointer to the "inner" value is returned. The
-    /// default initializer **must** be *infailable*, otherwise use
-    /// [`or_try_init()`](Self::or_try_init)!
-    /// 
-    /// Warning: This function [panics](mod@std::panic), if **no** *default*
-    /// initializer is available, or of the default initializer is *failable*!
-    pub fn or_init(&self) -> Arc<T> {
-        match &self.default_init {
-            DefaultInit::Infailable(init) => or_init_with(self.inner.write().unwrap(), || Arc::new(init())),
-            _ => panic!("No infailable default initializer!"),
-        }
-    }
-
-    /// Returns a pointer to the existing "inner" value, or tries to initialize
-    /// the value right now.
-    /// 
-    /// If and only if the "inner" value is **not** initialized yet, the
-    /// "inner" value is set to the return value of the *default* initializer
-    /// and a new `Arc<T>` pointer to the "inner" value is returned. If the
-    /// *default* initializer fails, the error is passed through.
-    /// 
-    /// If **no** *default* initializer is available, an error of type
-    /// [`NoDefaultInitializer`](crate::InitError) is returned.
-    pub fn or_try_init(&self) -> Result<Arc<T>, InitError> {
-        match &self.default_init {
-            DefaultInit::Infailable(init) => Ok(or_init_with(self.inner.write().unwrap(), || Arc::new(init()))),
-            DefaultInit::Failable(init) => match or_try_init_with(self.inner.write().unwrap(), || init().map(Arc::new)) {
-                Ok(value) => Ok(value),
-                Err(error) => Err(InitError::Failed(error)),
-            },
-            DefaultInit::None => Err(InitError::NoDefaultInitializer)
-        }
+        Self::with_failable_default_init_pointer(default_init)
     }
 
     /// Returns a pointer to the existing "inner" value, or initializes the
-    /// value right now.
-    /// 
-    /// If and only if the "inner" value is **not** initialized yet, the
-    /// function `init_fn()` is called to create the value. The "inner" value
-    /// is then set to the return value of `init_fn()` and a new `Arc<T>`
-    /// pointer to the "inner" value is returned.
-    pub fn or_init_with<F>(&self, init_fn: F) -> Arc<T>
+    /// value right now, using an initializer that returns a `Arc<T>` pointer.
+    ///
+    /// This works like [`or_init_with()`](Self::or_init_with), except that
+    /// `init_fn()` returns a `Arc<T>` pointer, or any other value that can be
+    /// converted into a `Arc<T>` pointer (e.g. `Box<T>`, `String` or `Vec<U>`).
+    /// Therefore, this function also supports *unsized* types, such as
+    /// `dyn Trait`, `str` or `[U]`.
+    pub fn or_init_with_arc<P, F>(&self, init_fn: F) -> Arc<T>
     where
-        F: FnOnce() -> T
+        P: Into<Arc<T>>,
+        F: FnOnce() -> P
     {
-        match self.value() {
-            Some(value) => value,
-            None => or_init_with(self.inner.write().unwrap(), || Arc::new(init_fn()))
-        }
+        self.or_init_with_pointer(init_fn)
     }
 
     /// Returns a pointer to the existing "inner" value, or tries to
-    /// initializes the value right now.
-    /// 
-    /// If and only if the "inner" value is **not** initialized yet, the
-    /// function `init_fn()` is called to create the value. In case that
-    /// `init_fn()` returns an error, that error is passed through and the
-    /// "inner" value remains in the *uninitialized* state for now. If the
-    /// "inner" value already existed or if it was created successfully just
-    /// now, a new `Arc<T>` pointer to the "inner" value is returned.
-    pub fn or_try_init_with<E, F>(&self, init_fn: F) -> Result<Arc<T>, E>
-    where
-        F: FnOnce() -> Result<T, E>
-    {
-        match self.value() {
-            Some(value) => Ok(value),
-            None => or_try_init_with(self.inner.write().unwrap(), || init_fn().map(Arc::new))
-        }
-    }
-
-    /// An alias for the [`or_init()`](Self::or_init) function.
-    pub fn unwrap(&self) -> Arc<T> {
-        self.or_init()
-    }
-    
-    /// Applies function `map_fn()` to the "inner", if already initialized.
-    /// 
-    /// If and only if the "inner" value already *is* initialize, the function
-    /// `map_fn()` is called with a reference to the "inner" value and its
-    /// return value is passed through. Otherwise the function `map_fn()` is
-    /// **not** called and `None` is returned.
-    pub fn map<U, F>(&self, map_fn: F) -> Option<U>
+    /// initializes the value right now, using an initializer that returns a
+    /// `Arc<T>` pointer.
+    ///
+    /// This works like [`or_try_init_with()`](Self::or_try_init_with), except
+    /// that `init_fn()` returns a `Arc<T>` pointer, or any other value that
+    /// can be converted into a `Arc<T>` pointer (e.g. `Box<T>` or `String`).
+    /// Therefore, this function also supports *unsized* types, such as
+    /// `dyn Trait`, `str` or `[U]`.
+    pub fn or_try_init_with_arc<P, E, F>(&self, init_fn: F) -> Result<Arc<T>, E>
     where
-        F: FnOnce(&Arc<T>) -> U
+        P: Into<Arc<T>>,
+        F: FnOnce() -> Result<P, E>
     {
-        self.inner.read().unwrap().as_ref().map(map_fn)
+        self.or_try_init_with_pointer(init_fn)
     }
 
-    /// Returns a pointer to the "inner" value, if already initialized.
-    /// 
-    /// If and only if the "inner" value already *is* initialized, the function
-    /// returns a new `Arc<T>` pointer to the "inner" value. Otherwise, if the
-    /// "inner" value is **not** initialized yet, the value remains in the
-    /// *uninitialized* state and the function returns `None`.
-    pub fn value(&self) -> Option<Arc<T>> {
-        self.inner.read().unwrap().as_ref().cloned()
-    }
-
-    /// Takes the "inner" value out of this `LazyArc<T>` instance, if already
-    /// initialized.
+    /// Converts this `LazyArc<T>` instance into a [`OnceLock`] that holds the
+    /// `Arc<T>` pointer to the "inner" value, if already initialized.
     ///
-    /// If and only if the "inner" value already *is* initialized, the function
-    /// returns the `Arc<T>` pointer to the current "inner" value and resets
-    /// this `LazyArc<T>` instance' "inner" value to the *uninitialized* state.
-    /// Otherwise, the function simply returns `None`.
-    pub fn take(&mut self) -> Option<Arc<T>> {
-        self.inner.get_mut().unwrap().take()
-    }
-}
-
-impl <T> Default for LazyArc<T> {
-    /// The default value is a new ***empty*** `LazyArc<T>` instance.
-    fn default() -> Self {
-        Self::empty()
-    }
-}
-
-impl <T> From<T> for LazyArc<T> {
-    /// Create a new `LazyArc<T>` that is already initialized to `value`.
-    fn from(value: T) -> Self {
-        Self {
-            inner: RwLock::new(Some(Arc::new(value))),
-            default_init: DefaultInit::None,
+    /// The *default* initializer (if any) is dropped. If the "inner" value is
+    /// **not** initialized yet, the returned `OnceLock` is *empty*.
+    pub fn into_once_lock(self) -> OnceLock<Arc<T>> {
+        match self.into_pointer() {
+            Some(value) => OnceLock::from(value),
+            None => OnceLock::new(),
         }
     }
 }
 
-impl <T> From<&T> for LazyArc<T>
-where
-    T: Clone,
-{
-    /// Create a new `LazyArc<T>` that is already initialized to `value`.
-    fn from(value: &T) -> Self {
-        Self {
-            inner: RwLock::new(Some(Arc::new(value.clone()))),
-            default_init: DefaultInit::None,
-        }
-    }
-}
-
-impl <T> From<Arc<T>> for LazyArc<T> {
+impl <T: ?Sized, L: LockFamily> From<Arc<T>> for Lazy<T, ArcFamily, L> {
     /// Create a new `LazyArc<T>` that is already initialized to `value`.
     fn from(value: Arc<T>) -> Self {
-        Self {
-            inner: RwLock::new(Some(value)),
-            default_init: DefaultInit::None,
-        }
+        Self::with_value(value)
     }
 }
 
-impl <T> From<&Arc<T>> for LazyArc<T> {
+impl <T: ?Sized, L: LockFamily> From<&Arc<T>> for Lazy<T, ArcFamily, L> {
     /// Create a new `LazyArc<T>` that is already initialized to `value`.
     fn from(value: &Arc<T>) -> Self {
-        Self {
-            inner: RwLock::new(Some(value.clone())),
-            default_init: DefaultInit::None,
-        }
+        Self::with_value(value.clone())
     }
 }
 
-impl<T> Clone for LazyArc<T> {
-    /// Creates a clone of this `LazyArc<T>` instance.
-    /// 
-    /// If the "inner" value of this instance *is* already initialized, the
-    /// clone will be pointing to the same "inner" value, i.e. the "inner"
-    /// value is **not** cloned. Otherwise, the clone will initially be
-    /// *empty*; it can be initialized ***independently*** from this instance.
-    fn clone(&self) -> LazyArc<T> {
-        match self.inner.read().unwrap().as_ref() {
-            Some(existing) => Self::from(existing),
-            None => Self::empty(),
+impl <T: ?Sized, L: LockFamily> From<OnceLock<Arc<T>>> for Lazy<T, ArcFamily, L> {
+    /// Create a new `LazyArc<T>` that is initialized to the value of `value`,
+    /// if the `OnceLock` is initialized, or that is *empty* otherwise.
+    fn from(value: OnceLock<Arc<T>>) -> Self {
+        match value.into_inner() {
+            Some(value) => Self::with_value(value),
+            None => Self::new(),
         }
     }
 }
 
-impl<T> Debug for LazyArc<T> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "LazyArc {{ default_init: {:?}, is_initialized: {:?} }}",
-            self.default_init,
-            self.inner.read().unwrap().is_some())
+impl <T, F, L> From<LazyLock<Arc<T>, F>> for Lazy<T, ArcFamily, L>
+where
+    T: ?Sized + Send + Sync + 'static,
+    F: FnOnce() -> Arc<T> + Send + 'static,
+    L: LockFamily<Threading = ThreadSafe>,
+{
+    /// Create a new `LazyArc<T>` whose *default* initializer forces `value`
+    /// and returns a pointer to its value.
+    ///
+    /// The `LazyArc<T>` is initially *empty*, even if `value` was already
+    /// forced. After a [reset](Lazy::reset), the *same* value is returned by
+    /// the `LazyLock` again.
+    fn from(value: LazyLock<Arc<T>, F>) -> Self {
+        Self::with_default_init_pointer(move || Arc::clone(LazyLock::force(&value)))
     }
 }
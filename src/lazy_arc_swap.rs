@@ -0,0 +1,367 @@
+/*
+ * lazy_rc - Rc<T> and Arc<T> with *lazy* initialization
+ * This is free and unencumbered software released into the public domain.
+ */
+use std::fmt::Debug;
+use std::io::{Result as IoResult};
+use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use arc_swap::ArcSwapOption;
+
+use crate::{CancellationToken, InitError};
+use crate::utils::{DefaultInit, call_infailable, or_init_with, or_try_init_with};
+
+type FnInit<T> = dyn Fn(&CancellationToken) -> Result<T, InitError> + Send + Sync;
+
+/// A thread-safe reference-counting pointer, akin to
+/// [`Arc<T>`](std::sync::Arc), but with ***lazy*** initialization and with
+/// support for *atomically* replacing the "inner" value
+/// 
+/// Unlike [`LazyArc<T>`](crate::LazyArc), readers of a `LazyArcSwap<T>`
+/// **never** block on a lock, not even while the "inner" value is being
+/// replaced by another thread. Only threads that *initialize* the "inner"
+/// value are serialized, so that the initializer runs at most once.
+/// 
+/// This type is only available with the `arc-swap` feature enabled.
+pub struct LazyArcSwap<T> {
+    inner: ArcSwapOption<T>,
+    init_lock: Mutex<()>,
+    default_init: DefaultInit<FnInit<Arc<T>>>,
+}
+
+/// The "inner" value of a [`LazyArcSwap<T>`], as seen while holding its
+/// `init_lock`, so that it can be initialized like the "inner" value of a
+/// [`Lazy<T, P, L>`](crate::Lazy)
+///
+/// A value that is inserted into the *empty* slot is published when the slot
+/// is dropped, unless another thread has stored a value in the meantime.
+struct InitSlot<'a, T> {
+    lazy: &'a LazyArcSwap<T>,
+    value: Option<Arc<T>>,
+    was_empty: bool,
+    _init_guard: MutexGuard<'a, ()>,
+}
+
+impl<T> LazyArcSwap<T> {
+    /// Create a new `LazyArcSwap<T>` that is initially *empty* and that
+    /// contains **no** *default* initializer.
+    /// 
+    /// The "inner" value will be [initialized](Self::or_init_with()) on first
+    /// access. Default initialization is **not** supported by this instance!
+    pub const fn empty() -> Self {
+        Self {
+            inner: ArcSwapOption::const_empty(),
+            init_lock: Mutex::new(()),
+            default_init: DefaultInit::None,
+        }
+    }
+
+    /// Create a new `LazyArcSwap<T>` that is initially *empty* and that
+    /// contains the given *default* initializer.
+    /// 
+    /// The "inner" value will be [initialized](Self::or_init_with()) on first
+    /// access. Default initialization *is* supported by this instance.
+    pub fn with_default_init<U>(default_init: U) -> Self
+    where
+        U: Fn() -> T + Send + Sync + 'static,
+    {
+        Self {
+            inner: ArcSwapOption::const_empty(),
+            init_lock: Mutex::new(()),
+            default_init: DefaultInit::Infailable(Box::new(move |_| Ok(Arc::new(default_init())))),
+        }
+    }
+
+    /// Create a new `LazyArcSwap<T>` that is initially *empty* and that
+    /// contains the given failable *default* initializer.
+    /// 
+    /// The "inner" value will be [initialized](Self::or_init_with()) on first
+    /// access. Default initialization *is* supported by this instance.
+    pub fn with_failable_default_init<U>(default_init: U) -> Self
+    where
+        U: Fn() -> IoResult<T> + Send + Sync + 'static,
+    {
+        Self {
+            inner: ArcSwapOption::const_empty(),
+            init_lock: Mutex::new(()),
+            default_init: DefaultInit::Failable(Box::new(move |_| default_init().map(Arc::new).map_err(InitError::Failed))),
+        }
+    }
+
+    /// Returns `true`, if and only if th "inner" value is initialized.
+    pub fn is_initialized(&self) -> bool {
+        self.inner.load().is_some()
+    }
+
+    /// Returns a pointer to the existing "inner" value, or tries to initialize
+    /// the value right now.
+    /// 
+    /// If and only if the "inner" value is **not** initialized yet, the
+    /// "inner" value is set to the return value of the *default* initializer
+    /// and a new `Arc<T>` pointer to the "inner" value is returned. The
+    /// default initializer **must** be *infailable*, otherwise use
+    /// [`or_try_init()`](Self::or_try_init)!
+    /// 
+    /// Warning: This function [panics](mod@std::panic), if **no** *default*
+    /// initializer is available, or of the default initializer is *failable*!
+    pub fn or_init(&self) -> Arc<T> {
+        match &self.default_init {
+            DefaultInit::Infailable(init) => self.or_init_with_arc(|| call_infailable(init.as_ref())),
+            _ => panic!("No infailable default initializer!"),
+        }
+    }
+
+    /// Returns a pointer to the existing "inner" value, or tries to initialize
+    /// the value right now.
+    /// 
+    /// If and only if the "inner" value is **not** initialized yet, the
+    /// "inner" value is set to the return value of the *default* initializer
+    /// and a new `Arc<T>` pointer to the "inner" value is returned. If the
+    /// *default* initializer fails, the error is passed through.
+    /// 
+    /// If **no** *default* initializer is available, an error of type
+    /// [`NoDefaultInitializer`](crate::InitError) is returned.
+    pub fn or_try_init(&self) -> Result<Arc<T>, InitError> {
+        match &self.default_init {
+            DefaultInit::Infailable(init) => Ok(self.or_init_with_arc(|| call_infailable(init.as_ref()))),
+            DefaultInit::None => Err(InitError::NoDefaultInitializer),
+            default_init => self.or_try_init_with_arc(|| default_init.call(&CancellationToken::new())),
+        }
+    }
+
+    /// Returns a pointer to the existing "inner" value, or initializes the
+    /// value right now.
+    /// 
+    /// If and only if the "inner" value is **not** initialized yet, the
+    /// function `init_fn()` is called to create the value. The "inner" value
+    /// is then set to the return value of `init_fn()` and a new `Arc<T>`
+    /// pointer to the "inner" value is returned.
+    pub fn or_init_with<F>(&self, init_fn: F) -> Arc<T>
+    where
+        F: FnOnce() -> T
+    {
+        self.or_init_with_arc(|| Arc::new(init_fn()))
+    }
+
+    /// Returns a pointer to the existing "inner" value, or tries to
+    /// initializes the value right now.
+    /// 
+    /// If and only if the "inner" value is **not** initialized yet, the
+    /// function `init_fn()` is called to create the value. In case that
+    /// `init_fn()` returns an error, that error is passed through and the
+    /// "inner" value remains in the *uninitialized* state for now. If the
+    /// "inner" value already existed or if it was created successfully just
+    /// now, a new `Arc<T>` pointer to the "inner" value is returned.
+    pub fn or_try_init_with<E, F>(&self, init_fn: F) -> Result<Arc<T>, E>
+    where
+        F: FnOnce() -> Result<T, E>
+    {
+        self.or_try_init_with_arc(|| init_fn().map(Arc::new))
+    }
+
+    /// An alias for the [`or_init()`](Self::or_init) function.
+    pub fn unwrap(&self) -> Arc<T> {
+        self.or_init()
+    }
+
+    /// Applies function `map_fn()` to the "inner", if already initialized.
+    /// 
+    /// If and only if the "inner" value already *is* initialize, the function
+    /// `map_fn()` is called with a reference to the "inner" value and its
+    /// return value is passed through. Otherwise the function `map_fn()` is
+    /// **not** called and `None` is returned.
+    pub fn map<U, F>(&self, map_fn: F) -> Option<U>
+    where
+        F: FnOnce(&Arc<T>) -> U
+    {
+        self.inner.load().as_ref().map(map_fn)
+    }
+
+    /// Returns a pointer to the "inner" value, if already initialized.
+    /// 
+    /// If and only if the "inner" value already *is* initialized, the function
+    /// returns a new `Arc<T>` pointer to the "inner" value. Otherwise, if the
+    /// "inner" value is **not** initialized yet, the value remains in the
+    /// *uninitialized* state and the function returns `None`.
+    pub fn value(&self) -> Option<Arc<T>> {
+        self.inner.load_full()
+    }
+
+    /// Atomically replaces the "inner" value with `value`.
+    /// 
+    /// The "inner" value is replaced, regardless of whether it already was
+    /// initialized or not. Existing `Arc<T>` pointers to the previous "inner"
+    /// value remain valid.
+    pub fn store<P>(&self, value: P)
+    where
+        P: Into<Arc<T>>,
+    {
+        self.inner.store(Some(value.into()))
+    }
+
+    /// Atomically replaces the "inner" value with `value` and returns the
+    /// pointer to the previous "inner" value, if it was initialized.
+    pub fn swap<P>(&self, value: P) -> Option<Arc<T>>
+    where
+        P: Into<Arc<T>>,
+    {
+        self.inner.swap(Some(value.into()))
+    }
+
+    /// Atomically replaces the "inner" value with `new`, if and only if the
+    /// "inner" value currently is `current`.
+    /// 
+    /// The previous "inner" value is returned, regardless of whether the swap
+    /// happened or not. Therefore, if the returned value is *pointer-equal*
+    /// to `current` (see [`Arc::ptr_eq()`]), then the swap happened.
+    pub fn compare_and_swap<P>(&self, current: &Option<Arc<T>>, new: P) -> Option<Arc<T>>
+    where
+        P: Into<Arc<T>>,
+    {
+        arc_swap::Guard::into_inner(self.inner.compare_and_swap(current, Some(new.into())))
+    }
+
+    /// Atomically updates the "inner" value by "read-copy-update".
+    /// 
+    /// The function `update_fn()` is called with a reference to the current
+    /// "inner" value (or `None`, if **not** initialized yet) and its return
+    /// value is stored as the new "inner" value. If the "inner" value was
+    /// replaced by another thread in the meantime, then `update_fn()` is
+    /// called *again* with the updated value, until the update succeeds.
+    /// 
+    /// The pointer to the previous "inner" value is returned.
+    pub fn rcu<P, F>(&self, mut update_fn: F) -> Option<Arc<T>>
+    where
+        P: Into<Arc<T>>,
+        F: FnMut(Option<&Arc<T>>) -> P,
+    {
+        self.inner.rcu(|current| Some(update_fn(current.as_ref()).into()))
+    }
+
+    /// Atomically resets the "inner" value to the *uninitialized* state.
+    /// 
+    /// The pointer to the previous "inner" value is returned, if it was
+    /// initialized. The next access will initialize the "inner" value again.
+    pub fn reset(&self) -> Option<Arc<T>> {
+        self.inner.swap(None)
+    }
+
+    /// Takes the "inner" value out of this `LazyArcSwap<T>` instance, if
+    /// already initialized.
+    /// 
+    /// If and only if the "inner" value already *is* initialized, the function
+    /// returns the `Arc<T>` pointer to the current "inner" value and resets
+    /// this `LazyArcSwap<T>` instance' "inner" value to the *uninitialized*
+    /// state. Otherwise, the function simply returns `None`.
+    pub fn take(&mut self) -> Option<Arc<T>> {
+        self.reset()
+    }
+
+    fn or_init_with_arc<F>(&self, init_fn: F) -> Arc<T>
+    where
+        F: FnOnce() -> Arc<T>
+    {
+        match self.value() {
+            Some(value) => value,
+            None => or_init_with(self.lock_for_init(), init_fn),
+        }
+    }
+
+    fn or_try_init_with_arc<E, F>(&self, init_fn: F) -> Result<Arc<T>, E>
+    where
+        F: FnOnce() -> Result<Arc<T>, E>
+    {
+        match self.value() {
+            Some(value) => Ok(value),
+            None => or_try_init_with(self.lock_for_init(), init_fn),
+        }
+    }
+
+    /// Acquires the `init_lock`, so that the initializer runs at most once.
+    fn lock_for_init(&self) -> InitSlot<'_, T> {
+        // A panicking initializer leaves the "inner" value uninitialized, so a
+        // poisoned lock is safe to use.
+        let init_guard = self.init_lock.lock().unwrap_or_else(PoisonError::into_inner);
+        let value = self.value();
+        InitSlot { lazy: self, was_empty: value.is_none(), value, _init_guard: init_guard }
+    }
+}
+
+impl<T> Deref for InitSlot<'_, T> {
+    type Target = Option<Arc<T>>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.value
+    }
+}
+
+impl<T> DerefMut for InitSlot<'_, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.value
+    }
+}
+
+impl<T> Drop for InitSlot<'_, T> {
+    fn drop(&mut self) {
+        if let (true, Some(value)) = (self.was_empty, self.value.take()) {
+            // A value may have been stored by another thread in the meantime!
+            self.lazy.inner.compare_and_swap(&None::<Arc<T>>, Some(value));
+        }
+    }
+}
+
+impl <T> Default for LazyArcSwap<T> {
+    /// The default value is a new ***empty*** `LazyArcSwap<T>` instance.
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+impl <T> From<T> for LazyArcSwap<T> {
+    /// Create a new `LazyArcSwap<T>` that is already initialized to `value`.
+    fn from(value: T) -> Self {
+        Self::from(Arc::new(value))
+    }
+}
+
+impl <T> From<Arc<T>> for LazyArcSwap<T> {
+    /// Create a new `LazyArcSwap<T>` that is already initialized to `value`.
+    fn from(value: Arc<T>) -> Self {
+        Self {
+            inner: ArcSwapOption::from(Some(value)),
+            init_lock: Mutex::new(()),
+            default_init: DefaultInit::None,
+        }
+    }
+}
+
+impl <T> From<&Arc<T>> for LazyArcSwap<T> {
+    /// Create a new `LazyArcSwap<T>` that is already initialized to `value`.
+    fn from(value: &Arc<T>) -> Self {
+        Self::from(value.clone())
+    }
+}
+
+impl<T> Clone for LazyArcSwap<T> {
+    /// Creates a clone of this `LazyArcSwap<T>` instance.
+    /// 
+    /// If the "inner" value of this instance *is* already initialized, the
+    /// clone will be pointing to the same "inner" value, i.e. the "inner"
+    /// value is **not** cloned. Otherwise, the clone will initially be
+    /// *empty*; it can be initialized ***independently*** from this instance.
+    fn clone(&self) -> LazyArcSwap<T> {
+        match self.value() {
+            Some(existing) => Self::from(existing),
+            None => Self::empty(),
+        }
+    }
+}
+
+impl<T> Debug for LazyArcSwap<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "LazyArcSwap {{ default_init: {:?}, is_initialized: {:?} }}",
+            self.default_init,
+            self.is_initialized())
+    }
+}
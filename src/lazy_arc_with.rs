@@ -0,0 +1,35 @@
+/*
+ * lazy_rc - Rc<T> and Arc<T> with *lazy* initialization
+ * This is free and unencumbered software released into the public domain.
+ */
+use std::io::{Error as IoError};
+
+use crate::{ArcFamily, LazyWith};
+use crate::lazy_arc::ArcLockFamily;
+
+/// A thread-safe reference-counting pointer, akin to
+/// [`Arc<T>`](std::sync::Arc), with ***lazy*** initialization, whose
+/// *default* initializer takes a context argument of type `A`
+///
+/// Unlike the *default* initializer of a [`LazyArc<T>`](crate::LazyArc),
+/// which must capture all of its context when the instance is created, the
+/// initializer of a `LazyArcWith<T, A>` receives the context on first access,
+/// via [`or_init_with_arg()`](LazyWith::or_init_with_arg). The initializer
+/// may be any closure that is `Send` and `Sync`:
+///
+/// ```
+/// use lazy_rc::LazyArcWith;
+/// use std::io::Error;
+///
+/// struct Config { name: String }
+/// struct Greeter { greeting: String }
+///
+/// let punctuation = String::from("!");
+/// let greeter: LazyArcWith<Greeter, Config> = LazyArcWith::new(move |config: &Config| {
+///     Ok::<_, Error>(Greeter { greeting: format!("Hello, {}{}", config.name, punctuation) })
+/// });
+///
+/// let config = Config { name: String::from("World") };
+/// assert_eq!(greeter.or_init_with_arg(&config).unwrap().greeting, "Hello, World!");
+/// ```
+pub type LazyArcWith<T, A, E = IoError> = LazyWith<T, A, E, ArcFamily, ArcLockFamily>;
@@ -0,0 +1,206 @@
+/*
+ * lazy_rc - Rc<T> and Arc<T> with *lazy* initialization
+ * This is free and unencumbered software released into the public domain.
+ */
+use std::fmt::Debug;
+use std::fs;
+#[cfg(unix)]
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard, TryLockError};
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::{InitError, LazyArc};
+use crate::sources;
+use crate::utils::BoxError;
+
+type FnLoad<T> = dyn Fn() -> Result<T, InitError> + Send + Sync;
+
+/// A thread-safe reference-counting pointer, akin to
+/// [`Arc<T>`](std::sync::Arc), whose "inner" value is ***lazily*** loaded
+/// from a file, and which is *reloaded* when the file changes
+/// 
+/// The "inner" value is loaded from the file on first access. Subsequent
+/// accesses check whether the file has been modified (or replaced) since it
+/// was loaded, and, if so, reload the "inner" value. In order to limit the
+/// overhead, the file is checked at most once per
+/// [check interval](Self::with_check_interval).
+/// 
+/// If reloading the file fails, the previous "inner" value is retained and
+/// the error is made available via [`last_error()`](Self::last_error).
+pub struct LazyFileArc<T> {
+    lazy: LazyArc<T>,
+    path: PathBuf,
+    loader: Box<FnLoad<T>>,
+    check_interval: Duration,
+    state: Mutex<ReloadState>,
+}
+
+struct ReloadState {
+    stamp: Option<FileStamp>,
+    last_check: Option<Instant>,
+    last_error: Option<Arc<InitError>>,
+}
+
+/// Identifies a specific *version* of a file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct FileStamp {
+    modified: Option<SystemTime>,
+    len: u64,
+    #[cfg(unix)]
+    inode: (u64, u64),
+}
+
+impl<T> LazyFileArc<T> {
+    /// The default interval at which the file is checked for changes.
+    pub const DEFAULT_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+    /// Create a new `LazyFileArc<T>` that is initially *empty* and that loads
+    /// its "inner" value from the file at `path`.
+    /// 
+    /// Whenever the file needs to be (re)loaded, it is read as UTF-8 text and
+    /// its content is passed to `parse_fn()`, which creates the new "inner"
+    /// value. Errors are reported as [`InitError::File`].
+    pub fn new<P, E, F>(path: P, parse_fn: F) -> Self
+    where
+        P: Into<PathBuf>,
+        E: Into<BoxError>,
+        F: Fn(&str) -> Result<T, E> + Send + Sync + 'static,
+    {
+        let path = path.into();
+        Self {
+            lazy: LazyArc::empty(),
+            loader: Box::new(sources::file(path.clone(), parse_fn)),
+            path,
+            check_interval: Self::DEFAULT_CHECK_INTERVAL,
+            state: Mutex::new(ReloadState { stamp: None, last_check: None, last_error: None }),
+        }
+    }
+
+    /// Sets the interval at which the file is checked for changes.
+    /// 
+    /// The file is checked at most once per `check_interval`; accesses that
+    /// happen in between return the current "inner" value right away.
+    pub fn with_check_interval(mut self, check_interval: Duration) -> Self {
+        self.check_interval = check_interval;
+        self
+    }
+
+    /// Returns the path of the file that the "inner" value is loaded from.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the underlying [`LazyArc<T>`], e.g. in order to
+    /// [subscribe](LazyArc::subscribe) to reloads.
+    pub fn lazy(&self) -> &LazyArc<T> {
+        &self.lazy
+    }
+
+    /// Returns a pointer to the current "inner" value, loading or reloading
+    /// the file as needed.
+    /// 
+    /// If the "inner" value is **not** initialized yet, the file is loaded
+    /// right now, and any error is passed through. Otherwise, if the check
+    /// interval has elapsed, the file is checked for changes and, if it has
+    /// changed, reloaded. If reloading fails, the *previous* "inner" value is
+    /// returned and the error is made available via
+    /// [`last_error()`](Self::last_error).
+    pub fn get(&self) -> Result<Arc<T>, InitError> {
+        if let Some(value) = self.lazy.value() {
+            return Ok(self.refresh(value));
+        }
+        let mut state = self.lock();
+        match self.lazy.value() {
+            Some(value) => Ok(value),
+            None => self.load(&mut state),
+        }
+    }
+
+    /// Returns a pointer to the current "inner" value, if already loaded,
+    /// without checking the file for changes.
+    pub fn value(&self) -> Option<Arc<T>> {
+        self.lazy.value()
+    }
+
+    /// Reloads the "inner" value from the file right now, regardless of
+    /// whether the file has changed or not.
+    /// 
+    /// If reloading fails, the *previous* "inner" value (if any) is retained
+    /// and the error is passed through. The error is also made available via
+    /// [`last_error()`](Self::last_error), just like for automatic reloads.
+    pub fn reload(&self) -> Result<Arc<T>, Arc<InitError>> {
+        let mut state = self.lock();
+        self.load(&mut state).map_err(|error| state.failed(error))
+    }
+
+    /// Returns the error of the most recent *failed* reload, or `None`, if
+    /// the most recent reload succeeded.
+    pub fn last_error(&self) -> Option<Arc<InitError>> {
+        self.lock().last_error.clone()
+    }
+
+    fn refresh(&self, current: Arc<T>) -> Arc<T> {
+        // If another thread is checking the file right now, don't wait for it
+        let mut state = match self.state.try_lock() {
+            Ok(state) => state,
+            Err(TryLockError::Poisoned(poisoned)) => poisoned.into_inner(),
+            Err(TryLockError::WouldBlock) => return current,
+        };
+        if state.last_check.is_some_and(|last_check| last_check.elapsed() < self.check_interval) {
+            return current;
+        }
+        state.last_check = Some(Instant::now());
+        if FileStamp::of(&self.path) == state.stamp {
+            return current;
+        }
+        match self.load(&mut state) {
+            Ok(value) => value,
+            Err(error) => {
+                state.failed(error);
+                current
+            },
+        }
+    }
+
+    fn load(&self, state: &mut ReloadState) -> Result<Arc<T>, InitError> {
+        // Take the stamp *before* reading, so that a concurrent change is detected next time
+        state.stamp = FileStamp::of(&self.path);
+        state.last_check = Some(Instant::now());
+        let value = Arc::new((self.loader)()?);
+        state.last_error = None;
+        self.lazy.replace(value.clone());
+        Ok(value)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, ReloadState> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl ReloadState {
+    /// Records the error of a *failed* reload.
+    fn failed(&mut self, error: InitError) -> Arc<InitError> {
+        self.last_error.insert(Arc::new(error)).clone()
+    }
+}
+
+impl FileStamp {
+    fn of(path: &Path) -> Option<Self> {
+        fs::metadata(path).ok().map(|metadata| Self {
+            modified: metadata.modified().ok(),
+            len: metadata.len(),
+            #[cfg(unix)]
+            inode: (metadata.dev(), metadata.ino()),
+        })
+    }
+}
+
+impl<T> Debug for LazyFileArc<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "LazyFileArc {{ path: {:?}, check_interval: {:?}, state: {:?} }}",
+            self.path,
+            self.check_interval,
+            self.lazy.state())
+    }
+}
@@ -0,0 +1,254 @@
+/*
+ * lazy_rc - Rc<T> and Arc<T> with *lazy* initialization
+ * This is free and unencumbered software released into the public domain.
+ */
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::io::{Error as IoError};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::LazyArc;
+
+type FnCompute<A, R, E> = dyn Fn(&A) -> Result<R, E> + Send + Sync;
+
+/// A thread-safe *memoized* function that computes its result ***lazily***,
+/// once per distinct argument
+///
+/// Each distinct argument is backed by its own [`LazyArc<R>`], i.e. the
+/// wrapped function is called on the *first* access for the argument, and
+/// all subsequent accesses for the same argument return a pointer to the
+/// *same* result. If multiple threads access the same argument concurrently,
+/// the function is called only *once*; the other threads wait for the result.
+/// If the function fails, the error is passed through, and the next access
+/// for the same argument tries again.
+///
+/// Optionally, the number of cached results can be bounded, in which case the
+/// *least recently used* result is evicted when the capacity is exceeded.
+pub struct LazyFn<A, R, E = IoError> {
+    function: Box<FnCompute<A, R, E>>,
+    capacity: Option<usize>,
+    entries: Mutex<Entries<A, Arc<LazyArc<R>>>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: AtomicU64,
+}
+
+/// The hit/miss statistics of a [`LazyFn<A, R>`] or
+/// [`LazyRcFn<A, R>`](crate::LazyRcFn)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// The number of accesses that returned an already computed result.
+    pub hits: u64,
+    /// The number of accesses that called the wrapped function.
+    pub misses: u64,
+    /// The number of results that were evicted due to the capacity bound.
+    pub evictions: u64,
+}
+
+/// The cached entries, with their *last used* ticks, and the arguments
+/// ordered by their *last used* ticks.
+pub(crate) struct Entries<A, L> {
+    map: HashMap<A, (L, u64)>,
+    order: BTreeMap<u64, A>,
+    tick: u64,
+}
+
+impl<A, R, E> LazyFn<A, R, E>
+where
+    A: Eq + Hash + Clone,
+{
+    /// Create a new `LazyFn<A, R>` that wraps the given function and whose
+    /// number of cached results is **not** bounded.
+    pub fn new<F>(function: F) -> Self
+    where
+        F: Fn(&A) -> Result<R, E> + Send + Sync + 'static,
+    {
+        Self::create(None, Box::new(function))
+    }
+
+    /// Create a new `LazyFn<A, R>` that wraps the given function and that
+    /// caches the results for at most `capacity` distinct arguments.
+    ///
+    /// If the capacity is exceeded, the *least recently used* result is
+    /// evicted. Pointers to an evicted result remain valid, but the next
+    /// access for the same argument calls the function again.
+    pub fn with_capacity<F>(capacity: usize, function: F) -> Self
+    where
+        F: Fn(&A) -> Result<R, E> + Send + Sync + 'static,
+    {
+        Self::create(Some(capacity), Box::new(function))
+    }
+
+    /// Returns a pointer to the result for `arg`, computing the result right
+    /// now, if **not** computed yet.
+    ///
+    /// If the function fails, the error is passed through, and **no** result
+    /// is cached for `arg`, i.e. the failed entry does **not** count toward
+    /// the capacity.
+    pub fn get(&self, arg: &A) -> Result<Arc<R>, E> {
+        let lazy = self.lock().access(arg, || Arc::new(LazyArc::empty()));
+        let mut computed = false;
+        let result = lazy.or_try_init_with(|| {
+            computed = true;
+            (self.function)(arg)
+        });
+        match computed {
+            true => self.misses.fetch_add(1, Ordering::Relaxed),
+            false => self.hits.fetch_add(1, Ordering::Relaxed),
+        };
+        let mut entries = self.lock();
+        match result {
+            Ok(_) => { self.evictions.fetch_add(entries.evict(self.capacity), Ordering::Relaxed); }
+            Err(_) => entries.remove_if(arg, |entry| Arc::ptr_eq(entry, &lazy) && !entry.is_initialized()),
+        }
+        drop(entries);
+        result
+    }
+
+    /// Returns a pointer to the result for `arg`, if already computed.
+    ///
+    /// This function does **not** count as an access, i.e. it neither updates
+    /// the statistics nor the *least recently used* order.
+    pub fn cached(&self, arg: &A) -> Option<Arc<R>> {
+        self.lock().get(arg).and_then(|lazy| lazy.value())
+    }
+
+    /// Removes the result for `arg` from the cache, returning a pointer to
+    /// the removed result, if any.
+    pub fn remove(&self, arg: &A) -> Option<Arc<R>> {
+        self.lock().remove(arg).and_then(|lazy| lazy.value())
+    }
+
+    /// Removes all results from the cache.
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    /// Returns the number of arguments that currently have a cache entry.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Returns `true`, if and only if the cache contains **no** entries.
+    pub fn is_empty(&self) -> bool {
+        self.lock().len() == 0
+    }
+
+    fn create(capacity: Option<usize>, function: Box<FnCompute<A, R, E>>) -> Self {
+        Self {
+            function,
+            capacity,
+            entries: Mutex::new(Entries::new()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            evictions: AtomicU64::new(0),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Entries<A, Arc<LazyArc<R>>>> {
+        self.entries.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl<A, R, E> LazyFn<A, R, E> {
+    /// Returns the capacity bound, or `None`, if the cache is unbounded.
+    pub fn capacity(&self) -> Option<usize> {
+        self.capacity
+    }
+
+    /// Returns the hit/miss statistics of this instance.
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            evictions: self.evictions.load(Ordering::Relaxed),
+        }
+    }
+}
+
+impl<A, L> Entries<A, L> {
+    pub fn new() -> Self {
+        Self {
+            map: HashMap::new(),
+            order: BTreeMap::new(),
+            tick: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+}
+
+impl<A, L: Clone> Entries<A, L>
+where
+    A: Eq + Hash + Clone,
+{
+    /// Returns the entry for `arg`, creating it if needed, and marks it as
+    /// the *most recently used* entry.
+    pub fn access<F>(&mut self, arg: &A, new_fn: F) -> L
+    where
+        F: FnOnce() -> L,
+    {
+        self.tick += 1;
+        let tick = self.tick;
+        if let Some((lazy, last_used)) = self.map.get_mut(arg) {
+            let key = self.order.remove(last_used).unwrap_or_else(|| arg.clone());
+            self.order.insert(tick, key);
+            *last_used = tick;
+            return lazy.clone();
+        }
+        let lazy = new_fn();
+        self.map.insert(arg.clone(), (lazy.clone(), tick));
+        self.order.insert(tick, arg.clone());
+        lazy
+    }
+
+    /// Evicts the *least recently used* entries, while `capacity` is exceeded.
+    /// Returns the number of evicted entries.
+    pub fn evict(&mut self, capacity: Option<usize>) -> u64 {
+        let mut evicted = 0;
+        while capacity.is_some_and(|capacity| self.map.len() > capacity.max(1)) {
+            if let Some((_, oldest)) = self.order.pop_first() {
+                self.map.remove(&oldest);
+                evicted += 1;
+            }
+        }
+        evicted
+    }
+
+    pub fn get(&self, arg: &A) -> Option<&L> {
+        self.map.get(arg).map(|(lazy, _)| lazy)
+    }
+
+    pub fn remove(&mut self, arg: &A) -> Option<L> {
+        let (lazy, last_used) = self.map.remove(arg)?;
+        self.order.remove(&last_used);
+        Some(lazy)
+    }
+
+    /// Removes the entry for `arg`, if and only if `predicate` holds for it.
+    pub fn remove_if<F>(&mut self, arg: &A, predicate: F)
+    where
+        F: FnOnce(&L) -> bool,
+    {
+        if self.get(arg).is_some_and(predicate) {
+            self.remove(arg);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.map.clear();
+        self.order.clear();
+    }
+}
+
+impl<A, R, E> Debug for LazyFn<A, R, E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let len = self.entries.lock().map(|entries| entries.len()).unwrap_or(0);
+        write!(f, "LazyFn {{ len: {:?}, capacity: {:?}, stats: {:?} }}", len, self.capacity, self.stats())
+    }
+}
+
@@ -0,0 +1,194 @@
+/*
+ * lazy_rc - Rc<T> and Arc<T> with *lazy* initialization
+ * This is free and unencumbered software released into the public domain.
+ */
+use std::cell::OnceCell;
+use std::ops::Deref;
+#[cfg(feature = "once_cell")]
+use std::rc::Rc;
+#[cfg(feature = "once_cell")]
+use std::sync::Arc;
+use std::sync::OnceLock;
+
+use crate::Lazy;
+#[cfg(feature = "once_cell")]
+use crate::{LazyArc, LazyRc};
+use crate::family::{LockFamily, PointerFamily};
+
+/// A cell whose value is initialized ***lazily***, i.e. on first access
+///
+/// This trait allows generic code to accept any kind of lazy cell, e.g. a
+/// [`LazyRc<T>`], a [`LazyArc<T>`], a [`OnceCell<T>`] or a [`OnceLock<T>`].
+/// With the `once_cell` feature enabled, it is also implemented by the
+/// `OnceCell<T>` types of the `once_cell` crate.
+///
+/// The value is accessed through [`Ref`](Self::Ref), which is a new
+/// `Arc<T>` or `Rc<T>` pointer for `LazyArc<T>` or `LazyRc<T>` (or, more
+/// generally, a new `P::Pointer<T>` for any [`Lazy<T, P, L>`]), and a plain
+/// reference for all other types.
+///
+/// Note: [`LazyLock<T>`](std::sync::LazyLock) and
+/// [`LazyCell<T>`](std::cell::LazyCell) do **not** implement this trait,
+/// because their initializer is fixed at construction time, so that
+/// `get_or_init_with()` could **not** honor `init_fn()`, and because `get()`
+/// can **not** be implemented on stable Rust without forcing the value. The
+/// same applies to the `Lazy<T>` types of the `once_cell` crate. Instead, a
+/// `LazyLock<Arc<T>>` can be converted into a `LazyArc<T>`, and a
+/// `LazyCell<Rc<T>>` into a `LazyRc<T>`, whose *default* initializer forces
+/// the original cell.
+pub trait LazyInit<T> {
+    /// The type through which the value is accessed.
+    type Ref<'a>: Deref<Target = T> where Self: 'a;
+
+    /// Returns the value, if already initialized, or `None` otherwise.
+    fn get(&self) -> Option<Self::Ref<'_>>;
+
+    /// Returns the value, initializing it right now via `init_fn()`, if
+    /// **not** initialized yet.
+    fn get_or_init_with<F: FnOnce() -> T>(&self, init_fn: F) -> Self::Ref<'_>;
+
+    /// Returns `true`, if and only if the value is initialized.
+    fn is_initialized(&self) -> bool {
+        self.get().is_some()
+    }
+}
+
+impl<T, P: PointerFamily, L: LockFamily> LazyInit<T> for Lazy<T, P, L> {
+    type Ref<'a> = P::Pointer<T> where Self: 'a;
+
+    fn get(&self) -> Option<P::Pointer<T>> {
+        self.value()
+    }
+
+    fn get_or_init_with<F: FnOnce() -> T>(&self, init_fn: F) -> P::Pointer<T> {
+        self.or_init_with(init_fn)
+    }
+
+    fn is_initialized(&self) -> bool {
+        Lazy::is_initialized(self)
+    }
+}
+
+impl<T> LazyInit<T> for OnceLock<T> {
+    type Ref<'a> = &'a T where T: 'a;
+
+    fn get(&self) -> Option<&T> {
+        OnceLock::get(self)
+    }
+
+    fn get_or_init_with<F: FnOnce() -> T>(&self, init_fn: F) -> &T {
+        self.get_or_init(init_fn)
+    }
+}
+
+impl<T> LazyInit<T> for OnceCell<T> {
+    type Ref<'a> = &'a T where T: 'a;
+
+    fn get(&self) -> Option<&T> {
+        OnceCell::get(self)
+    }
+
+    fn get_or_init_with<F: FnOnce() -> T>(&self, init_fn: F) -> &T {
+        self.get_or_init(init_fn)
+    }
+}
+
+#[cfg(feature = "once_cell")]
+impl<T> LazyInit<T> for once_cell::sync::OnceCell<T> {
+    type Ref<'a> = &'a T where T: 'a;
+
+    fn get(&self) -> Option<&T> {
+        once_cell::sync::OnceCell::get(self)
+    }
+
+    fn get_or_init_with<F: FnOnce() -> T>(&self, init_fn: F) -> &T {
+        self.get_or_init(init_fn)
+    }
+}
+
+#[cfg(feature = "once_cell")]
+impl<T> LazyInit<T> for once_cell::unsync::OnceCell<T> {
+    type Ref<'a> = &'a T where T: 'a;
+
+    fn get(&self) -> Option<&T> {
+        once_cell::unsync::OnceCell::get(self)
+    }
+
+    fn get_or_init_with<F: FnOnce() -> T>(&self, init_fn: F) -> &T {
+        self.get_or_init(init_fn)
+    }
+}
+
+#[cfg(feature = "once_cell")]
+impl<T: ?Sized> From<once_cell::sync::OnceCell<Arc<T>>> for LazyArc<T> {
+    /// Create a new `LazyArc<T>` that is initialized to the value of `value`,
+    /// if the `OnceCell` is initialized, or that is *empty* otherwise.
+    fn from(value: once_cell::sync::OnceCell<Arc<T>>) -> Self {
+        match value.into_inner() {
+            Some(value) => Self::from(value),
+            None => Self::empty(),
+        }
+    }
+}
+
+#[cfg(feature = "once_cell")]
+impl<T: ?Sized> From<once_cell::unsync::OnceCell<Rc<T>>> for LazyRc<T> {
+    /// Create a new `LazyRc<T>` that is initialized to the value of `value`,
+    /// if the `OnceCell` is initialized, or that is *empty* otherwise.
+    fn from(value: once_cell::unsync::OnceCell<Rc<T>>) -> Self {
+        match value.into_inner() {
+            Some(value) => Self::from(value),
+            None => Self::empty(),
+        }
+    }
+}
+
+#[cfg(feature = "once_cell")]
+impl<T, F> From<once_cell::sync::Lazy<Arc<T>, F>> for LazyArc<T>
+where
+    T: ?Sized + Send + Sync + 'static,
+    F: FnOnce() -> Arc<T> + Send + 'static,
+{
+    /// Create a new `LazyArc<T>` whose *default* initializer forces `value`
+    /// and returns a pointer to its value.
+    fn from(value: once_cell::sync::Lazy<Arc<T>, F>) -> Self {
+        Self::with_default_init_pointer(move || Arc::clone(once_cell::sync::Lazy::force(&value)))
+    }
+}
+
+#[cfg(feature = "once_cell")]
+impl<T, F> From<once_cell::unsync::Lazy<Rc<T>, F>> for LazyRc<T>
+where
+    T: ?Sized + 'static,
+    F: FnOnce() -> Rc<T> + 'static,
+{
+    /// Create a new `LazyRc<T>` whose *default* initializer forces `value`
+    /// and returns a pointer to its value.
+    fn from(value: once_cell::unsync::Lazy<Rc<T>, F>) -> Self {
+        Self::with_default_init_pointer(move || Rc::clone(once_cell::unsync::Lazy::force(&value)))
+    }
+}
+
+#[cfg(feature = "once_cell")]
+impl<T: ?Sized> From<LazyArc<T>> for once_cell::sync::OnceCell<Arc<T>> {
+    /// Converts a `LazyArc<T>` into a `OnceCell` that holds the pointer to the
+    /// "inner" value, if already initialized, see [`LazyArc::into_once_lock()`].
+    fn from(value: LazyArc<T>) -> Self {
+        match value.into_once_lock().into_inner() {
+            Some(value) => Self::with_value(value),
+            None => Self::new(),
+        }
+    }
+}
+
+#[cfg(feature = "once_cell")]
+impl<T: ?Sized> From<LazyRc<T>> for once_cell::unsync::OnceCell<Rc<T>> {
+    /// Converts a `LazyRc<T>` into a `OnceCell` that holds the pointer to the
+    /// "inner" value, if already initialized, see [`LazyRc::into_once_cell()`].
+    fn from(value: LazyRc<T>) -> Self {
+        match value.into_once_cell().into_inner() {
+            Some(value) => Self::with_value(value),
+            None => Self::new(),
+        }
+    }
+}
@@ -2,247 +2,169 @@
  * lazy_rc - Rc<T> and Arc<T> with *lazy* initialization
  * This is free and unencumbered software released into the public domain.
  */
-use std::fmt::Debug;
+use std::cell::{LazyCell, OnceCell};
+use std::future::Future;
 use std::io::{Result as IoResult};
 use std::rc::Rc;
-use std::cell::RefCell;
 
-use crate::InitError;
-use crate::utils::{DefaultInit, or_init_with, or_try_init_with};
+use crate::{Handle, Lazy};
+use crate::family::{Captured, LockFamily, RcFamily, RefCellFamily, SingleThreaded};
 
 /// A single-threaded reference-counting pointer, akin to
 /// [`Rc<T>`](std::rc::Rc), but with ***lazy*** initialization
-pub struct LazyRc<T> {
-    inner: RefCell<Option<Rc<T>>>,
-    default_init: DefaultInit<T>,
-}
+///
+/// This is an alias for a [`Lazy<T, P, L>`] that uses `Rc<T>` pointers and a
+/// `RefCell`, i.e. accessing the instance from within its own initializer
+/// [panics](mod@std::panic).
+pub type LazyRc<T> = Lazy<T, RcFamily, RefCellFamily>;
 
-impl<T> LazyRc<T> {
-    /// Create a new `LazyRc<T>` that is initially *empty* and that contains
-    /// **no** *default* initializer.
-    /// 
-    /// The "inner" value will be [initialized](Self::or_init_with()) on first
-    /// access. Default initialization is **not** supported by this instance!
-    pub const fn empty() -> Self {
-        Self {
-            inner: RefCell::new(None),
-            default_init: DefaultInit::None,
-        }
-    }
+/// A pointer to the "inner" value of a [`LazyRc<T>`], which remembers the
+/// [generation](`Lazy::generation`) of the value, see [`Handle`].
+pub type RcHandle<'a, T> = Handle<'a, T, RcFamily, RefCellFamily>;
 
+impl<T: ?Sized, L: LockFamily> Lazy<T, RcFamily, L> {
     /// Create a new `LazyRc<T>` that is initially *empty* and that contains
-    /// the given *default* initializer.
-    /// 
-    /// The "inner" value will be [initialized](Self::or_init_with()) on first
-    /// access. Default initialization *is* supported by this instance.
-    pub fn with_default_init<U>(default_init: U) -> Self
+    /// the given *default* initializer, which returns a `Rc<T>` pointer, or
+    /// any other value that can be converted into a `Rc<T>` pointer.
+    ///
+    /// Unlike [`with_default_init()`](Self::with_default_init), this function
+    /// also supports *unsized* types, such as `dyn Trait`, `str` or `[T]`.
+    pub fn with_default_init_rc<U, P>(default_init: U) -> Self
     where
-        U: Fn() -> T + Sync + 'static,
+        U: Fn() -> P + 'static,
+        P: Into<Rc<T>>,
+        Captured<L, U>: Send + Sync,
     {
-        Self {
-            inner: RefCell::new(None),
-            default_init: DefaultInit::Infailable(Box::new(default_init)),
-        }
+        Self::with_default_init_pointer(default_init)
     }
 
     /// Create a new `LazyRc<T>` that is initially *empty* and that contains
-    /// the given failable *default* initializer.
-    /// 
-    /// The "inner" value will be [initialized](Self::or_init_with()) on first
-    /// access. Default initialization *is* supported by this instance.
-    pub fn with_failable_default_init<U>(default_init: U) -> Self
+    /// the given failable *default* initializer, which returns a `Rc<T>`
+    /// pointer, or any other value that can be converted into a `Rc<T>`.
+    ///
+    /// Unlike [`with_failable_default_init()`](Self::with_failable_default_init),
+    /// this function also supports *unsized* types, such as `dyn Trait`.
+    pub fn with_failable_default_init_rc<U, P>(default_init: U) -> Self
     where
-        U: Fn() -> IoResult<T> + Sync + 'static,
+        U: Fn() -> IoResult<P> + 'static,
+        P: Into<Rc<T>>,
+        Captured<L, U>: Send + Sync,
     {
-        Self {
-            inner: RefCell::new(None),
-            default_init: DefaultInit::Failable(Box::new(default_init)),
-        }
-    }
-
-    /// Returns `true`, if and only if th "inner" value is initialized.
-    pub fn is_initialized(&self) -> bool {
-        self.inner.borrow().is_some()
-    }
-
-    /// Returns a pointer to the existing "inner" value, or tries to initialize
-    /// the value right now.
-    /// 
-    /// If and only if the "inner" value is **not** initialized yet, the
-    /// "inner" value is set to the return value of the *default* initializer
-    /// and a new `Rc<T>` pointer to the "inner" value is returned. The
-    /// default initializer **must** be *infailable*, otherwise use
-    /// [`or_try_init()`](Self::or_try_init)!
-    /// 
-    /// Warning: This function [panics](mod@std::panic), if **no** *default*
-    /// initializer is available, or of the default initializer is *failable*!
-    pub fn or_init(&self) -> Rc<T> {
-        match &self.default_init {
-            DefaultInit::Infailable(init) => or_init_with(self.inner.borrow_mut(), || Rc::new(init())),
-            _ => panic!("No infailable default initializer!"),
-        }
-    }
-
-    /// Returns a pointer to the existing "inner" value, or tries to initialize
-    /// the value right now.
-    /// 
-    /// If and only if the "inner" value is **not** initialized yet, the
-    /// "inner" value is set to the return value of the *default* initializer
-    /// and a new `Rc<T>` pointer to the "inner" value is returned. If the
-    /// *default* initializer fails, the error is passed through.
-    /// 
-    /// If **no** *default* initializer is available, an error of type
-    /// [`NoDefaultInitializer`](crate::InitError) is returned.
-    pub fn or_try_init(&self) -> Result<Rc<T>, InitError> {
-        match &self.default_init {
-            DefaultInit::Infailable(init) => Ok(or_init_with(self.inner.borrow_mut(), || Rc::new(init()))),
-            DefaultInit::Failable(init) => match or_try_init_with(self.inner.borrow_mut(), || init().map(Rc::new)) {
-                Ok(value) => Ok(value),
-                Err(error) => Err(InitError::Failed(error)),
-            },
-            DefaultInit::None => Err(InitError::NoDefaultInitializer)
-        }
+        Self::with_failable_default_init_pointer(default_init)
     }
 
     /// Returns a pointer to the existing "inner" value, or initializes the
-    /// value right now.
-    /// 
-    /// If and only if the "inner" value is **not** initialized yet, the
-    /// function `init_fn()` is called to create the value. The "inner" value
-    /// is then set to the return value of `init_fn()` and a new `Rc<T>`
-    /// pointer to the "inner" value is returned.
-    pub fn or_init_with<F>(&self, init_fn: F) -> Rc<T>
+    /// value right now, using an initializer that returns a `Rc<T>` pointer.
+    ///
+    /// This works like [`or_init_with()`](Self::or_init_with), except that
+    /// `init_fn()` returns a `Rc<T>` pointer, or any other value that can be
+    /// converted into a `Rc<T>` pointer (e.g. `Box<T>`, `String` or `Vec<U>`).
+    /// Therefore, this function also supports *unsized* types, such as
+    /// `dyn Trait`, `str` or `[U]`.
+    pub fn or_init_with_rc<P, F>(&self, init_fn: F) -> Rc<T>
     where
-        F: FnOnce() -> T
+        P: Into<Rc<T>>,
+        F: FnOnce() -> P
     {
-        or_init_with(self.inner.borrow_mut(), || Rc::new(init_fn()))
+        self.or_init_with_pointer(init_fn)
     }
 
     /// Returns a pointer to the existing "inner" value, or tries to
-    /// initializes the value right now.
-    /// 
-    /// If and only if the "inner" value is **not** initialized yet, the
-    /// function `init_fn()` is called to create the value. In case that
-    /// `init_fn()` returns an error, that error is passed through and the
-    /// "inner" value remains in the *uninitialized* state for now. If the
-    /// "inner" value already existed or if it was created successfully just
-    /// now, a new `Rc<T>` pointer to the "inner" value is returned.
-    pub fn or_try_init_with<E, F>(&self, init_fn: F) -> Result<Rc<T>, E>
-    where
-        F: FnOnce() -> Result<T, E>
-    {
-        or_try_init_with(self.inner.borrow_mut(), || init_fn().map(Rc::new))
-    }
-
-    /// An alias for the [`or_init()`](Self::or_init) function.
-    pub fn unwrap(&self) -> Rc<T> {
-        self.or_init()
-    }
-
-    /// Applies function `map_fn()` to the "inner", if already initialized.
-    /// 
-    /// If and only if the "inner" value already *is* initialize, the function
-    /// `map_fn()` is called with a reference to the "inner" value and its
-    /// return value is passed through. Otherwise the function `map_fn()` is
-    /// **not** called and `None` is returned.
-    pub fn map<U, F>(&self, map_fn: F) -> Option<U>
+    /// initializes the value right now, using an initializer that returns a
+    /// `Rc<T>` pointer.
+    ///
+    /// This works like [`or_try_init_with()`](Self::or_try_init_with), except
+    /// that `init_fn()` returns a `Rc<T>` pointer, or any other value that
+    /// can be converted into a `Rc<T>` pointer (e.g. `Box<T>` or `String`).
+    /// Therefore, this function also supports *unsized* types, such as
+    /// `dyn Trait`, `str` or `[U]`.
+    pub fn or_try_init_with_rc<P, E, F>(&self, init_fn: F) -> Result<Rc<T>, E>
     where
-        F: FnOnce(&Rc<T>) -> U
+        P: Into<Rc<T>>,
+        F: FnOnce() -> Result<P, E>
     {
-        self.inner.borrow().as_ref().map(map_fn)
+        self.or_try_init_with_pointer(init_fn)
     }
 
-    /// Returns a pointer to the "inner" value, if already initialized.
-    /// 
-    /// If and only if the "inner" value already *is* initialized, the function
-    /// returns a new `Rc<T>` pointer to the "inner" value. Otherwise, if the
-    /// "inner" value is **not** initialized yet, the value remains in the
-    /// *uninitialized* state and the function returns `None`.
-    pub fn value(&self) -> Option<Rc<T>> {
-        self.inner.borrow().as_ref().cloned()
-    }
-
-    /// Takes the "inner" value out of this `LazyRc<T>` instance, if already
-    /// initialized.
+    /// Converts this `LazyRc<T>` instance into a [`OnceCell`] that holds the
+    /// `Rc<T>` pointer to the "inner" value, if already initialized.
     ///
-    /// If and only if the "inner" value already *is* initialized, the function
-    /// returns the `Rc<T>` pointer to the current "inner" value and resets
-    /// this `LazyRc<T>` instance' "inner" value to the *uninitialized* state.
-    /// Otherwise, the function simply returns `None`.
-    pub fn take(&mut self) -> Option<Rc<T>> {
-        self.inner.get_mut().take()
-    }
-}
-
-impl <T> Default for LazyRc<T> {
-    /// The default value is a new ***empty*** `LazyRc<T>` instance.
-    fn default() -> Self {
-        Self::empty()
-    }
-}
-
-impl <T> From<T> for LazyRc<T> {
-    /// Create a new `LazyRc<T>` that is already initialized to `value`.
-    fn from(value: T) -> Self {
-        Self {
-            inner: RefCell::new(Some(Rc::new(value))),
-            default_init: DefaultInit::None,
+    /// The *default* initializer (if any) is dropped. If the "inner" value is
+    /// **not** initialized yet, the returned `OnceCell` is *empty*.
+    pub fn into_once_cell(self) -> OnceCell<Rc<T>> {
+        match self.into_pointer() {
+            Some(value) => OnceCell::from(value),
+            None => OnceCell::new(),
         }
     }
 }
 
-impl <T> From<&T> for LazyRc<T>
-where
-    T: Clone,
-{
-    /// Create a new `LazyRc<T>` that is already initialized to `value`.
-    fn from(value: &T) -> Self {
-        Self {
-            inner: RefCell::new(Some(Rc::new(value.clone()))),
-            default_init: DefaultInit::None,
-        }
+impl<T, L: LockFamily> Lazy<T, RcFamily, L> {
+    /// Returns a pointer to the existing "inner" value, or tries to
+    /// initializes the value right now, by awaiting the future that is
+    /// returned by `init_fn()`.
+    ///
+    /// The future does **not** need to be [`Send`], so this function is
+    /// suitable for single-threaded executors, e.g. a tokio `LocalSet`. The
+    /// "inner" value is **not** borrowed while the future is pending. If
+    /// another task is initializing the "inner" value already, the current
+    /// task waits for that initialization to complete, rather than calling
+    /// its own `init_fn()`, so that concurrent tasks share one initialization.
+    /// Only if the pending initialization fails (or is cancelled, by dropping
+    /// its future), the next waiting task calls its own `init_fn()`.
+    ///
+    /// In case that `init_fn()` returns an error, that error is passed through
+    /// and the "inner" value remains in the *uninitialized* state for now.
+    ///
+    /// Note: *Synchronous* accessors, such as [`or_init()`](Self::or_init), do
+    /// **not** wait for a pending *asynchronous* initialization.
+    pub async fn or_try_init_with_async<E, F, U>(&self, init_fn: F) -> Result<Rc<T>, E>
+    where
+        F: FnOnce() -> U,
+        U: Future<Output = Result<T, E>>,
+    {
+        self.try_init_async(|| async { init_fn().await.map(Rc::new) }).await
     }
 }
 
-impl <T> From<Rc<T>> for LazyRc<T> {
+impl <T: ?Sized, L: LockFamily> From<Rc<T>> for Lazy<T, RcFamily, L> {
     /// Create a new `LazyRc<T>` that is already initialized to `value`.
     fn from(value: Rc<T>) -> Self {
-        Self {
-            inner: RefCell::new(Some(value)),
-            default_init: DefaultInit::None,
-        }
+        Self::with_value(value)
     }
 }
 
-impl <T> From<&Rc<T>> for LazyRc<T> {
+impl <T: ?Sized, L: LockFamily> From<&Rc<T>> for Lazy<T, RcFamily, L> {
     /// Create a new `LazyRc<T>` that is already initialized to `value`.
     fn from(value: &Rc<T>) -> Self {
-        Self {
-            inner: RefCell::new(Some(value.clone())),
-            default_init: DefaultInit::None,
-        }
+        Self::with_value(value.clone())
     }
 }
 
-impl<T> Clone for LazyRc<T> {
-    /// Creates a clone of this `LazyRc<T>` instance.
-    /// 
-    /// If the "inner" value of this instance *is* already initialized, the
-    /// clone will be pointing to the same "inner" value, i.e. the "inner"
-    /// value is **not** cloned. Otherwise, the clone will initially be
-    /// *empty*; it can be initialized ***independently*** from this instance.
-    fn clone(&self) -> LazyRc<T> {
-        match self.inner.borrow().as_ref() {
-            Some(existing) => Self::from(existing),
-            _ => Self::empty(),
+impl <T: ?Sized, L: LockFamily> From<OnceCell<Rc<T>>> for Lazy<T, RcFamily, L> {
+    /// Create a new `LazyRc<T>` that is initialized to the value of `value`,
+    /// if the `OnceCell` is initialized, or that is *empty* otherwise.
+    fn from(value: OnceCell<Rc<T>>) -> Self {
+        match value.into_inner() {
+            Some(value) => Self::with_value(value),
+            None => Self::new(),
         }
     }
 }
 
-impl<T> Debug for LazyRc<T> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "LazyRc {{ default_init: {:?}, is_initialized: {:?} }}",
-            self.default_init,
-            self.inner.borrow().is_some())
+impl <T, F, L> From<LazyCell<Rc<T>, F>> for Lazy<T, RcFamily, L>
+where
+    T: ?Sized + 'static,
+    F: FnOnce() -> Rc<T> + 'static,
+    L: LockFamily<Threading = SingleThreaded>,
+{
+    /// Create a new `LazyRc<T>` whose *default* initializer forces `value`
+    /// and returns a pointer to its value.
+    ///
+    /// The `LazyRc<T>` is initially *empty*, even if `value` was already
+    /// forced. After a [reset](Lazy::reset), the *same* value is returned by
+    /// the `LazyCell` again.
+    fn from(value: LazyCell<Rc<T>, F>) -> Self {
+        Self::with_default_init_pointer(move || Rc::clone(LazyCell::force(&value)))
     }
 }
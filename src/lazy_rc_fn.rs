@@ -0,0 +1,148 @@
+/*
+ * lazy_rc - Rc<T> and Arc<T> with *lazy* initialization
+ * This is free and unencumbered software released into the public domain.
+ */
+use std::cell::{Cell, RefCell};
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::io::{Error as IoError};
+use std::rc::Rc;
+
+use crate::{CacheStats, LazyRc};
+use crate::lazy_fn::Entries;
+
+type FnCompute<A, R, E> = dyn Fn(&A) -> Result<R, E>;
+
+/// A single-threaded *memoized* function that computes its result
+/// ***lazily***, once per distinct argument
+///
+/// This works like [`LazyFn<A, R>`](crate::LazyFn), except that each distinct
+/// argument is backed by its own [`LazyRc<R>`], and that the wrapped function
+/// does **not** need to be `Send` or `Sync`.
+///
+/// The wrapped function must **not** (directly or indirectly) call
+/// [`get()`](Self::get) for the *same* argument that it is currently computing
+/// the result for, otherwise the thread will panic!
+pub struct LazyRcFn<A, R, E = IoError> {
+    function: Box<FnCompute<A, R, E>>,
+    capacity: Option<usize>,
+    entries: RefCell<Entries<A, Rc<LazyRc<R>>>>,
+    hits: Cell<u64>,
+    misses: Cell<u64>,
+    evictions: Cell<u64>,
+}
+
+impl<A, R, E> LazyRcFn<A, R, E>
+where
+    A: Eq + Hash + Clone,
+{
+    /// Create a new `LazyRcFn<A, R>` that wraps the given function and whose
+    /// number of cached results is **not** bounded.
+    pub fn new<F>(function: F) -> Self
+    where
+        F: Fn(&A) -> Result<R, E> + 'static,
+    {
+        Self::create(None, Box::new(function))
+    }
+
+    /// Create a new `LazyRcFn<A, R>` that wraps the given function and that
+    /// caches the results for at most `capacity` distinct arguments.
+    ///
+    /// If the capacity is exceeded, the *least recently used* result is
+    /// evicted. Pointers to an evicted result remain valid, but the next
+    /// access for the same argument calls the function again.
+    pub fn with_capacity<F>(capacity: usize, function: F) -> Self
+    where
+        F: Fn(&A) -> Result<R, E> + 'static,
+    {
+        Self::create(Some(capacity), Box::new(function))
+    }
+
+    /// Returns a pointer to the result for `arg`, computing the result right
+    /// now, if **not** computed yet.
+    ///
+    /// If the function fails, the error is passed through, and **no** result
+    /// is cached for `arg`, i.e. the failed entry does **not** count toward
+    /// the capacity.
+    pub fn get(&self, arg: &A) -> Result<Rc<R>, E> {
+        let lazy = self.entries.borrow_mut().access(arg, || Rc::new(LazyRc::empty()));
+        let mut computed = false;
+        let result = lazy.or_try_init_with(|| {
+            computed = true;
+            (self.function)(arg)
+        });
+        let counter = if computed { &self.misses } else { &self.hits };
+        counter.set(counter.get() + 1);
+        let mut entries = self.entries.borrow_mut();
+        match result {
+            Ok(_) => self.evictions.set(self.evictions.get() + entries.evict(self.capacity)),
+            Err(_) => entries.remove_if(arg, |entry| Rc::ptr_eq(entry, &lazy) && !entry.is_initialized()),
+        }
+        drop(entries);
+        result
+    }
+
+    /// Returns a pointer to the result for `arg`, if already computed.
+    ///
+    /// This function does **not** count as an access, i.e. it neither updates
+    /// the statistics nor the *least recently used* order.
+    pub fn cached(&self, arg: &A) -> Option<Rc<R>> {
+        self.entries.borrow().get(arg).and_then(|lazy| lazy.value())
+    }
+
+    /// Removes the result for `arg` from the cache, returning a pointer to
+    /// the removed result, if any.
+    pub fn remove(&self, arg: &A) -> Option<Rc<R>> {
+        let lazy = self.entries.borrow_mut().remove(arg);
+        lazy.and_then(|lazy| lazy.value())
+    }
+
+    /// Removes all results from the cache.
+    pub fn clear(&self) {
+        self.entries.borrow_mut().clear();
+    }
+
+    /// Returns the number of arguments that currently have a cache entry.
+    pub fn len(&self) -> usize {
+        self.entries.borrow().len()
+    }
+
+    /// Returns `true`, if and only if the cache contains **no** entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.borrow().len() == 0
+    }
+
+    fn create(capacity: Option<usize>, function: Box<FnCompute<A, R, E>>) -> Self {
+        Self {
+            function,
+            capacity,
+            entries: RefCell::new(Entries::new()),
+            hits: Cell::new(0),
+            misses: Cell::new(0),
+            evictions: Cell::new(0),
+        }
+    }
+}
+
+impl<A, R, E> LazyRcFn<A, R, E> {
+    /// Returns the capacity bound, or `None`, if the cache is unbounded.
+    pub fn capacity(&self) -> Option<usize> {
+        self.capacity
+    }
+
+    /// Returns the hit/miss statistics of this instance.
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.get(),
+            misses: self.misses.get(),
+            evictions: self.evictions.get(),
+        }
+    }
+}
+
+impl<A, R, E> Debug for LazyRcFn<A, R, E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let len = self.entries.try_borrow().map(|entries| entries.len()).unwrap_or(0);
+        write!(f, "LazyRcFn {{ len: {:?}, capacity: {:?}, stats: {:?} }}", len, self.capacity, self.stats())
+    }
+}
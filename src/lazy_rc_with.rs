@@ -0,0 +1,35 @@
+/*
+ * lazy_rc - Rc<T> and Arc<T> with *lazy* initialization
+ * This is free and unencumbered software released into the public domain.
+ */
+use std::io::{Error as IoError};
+
+use crate::{LazyWith, RcFamily, RefCellFamily};
+
+/// A single-threaded reference-counting pointer, akin to
+/// [`Rc<T>`](std::rc::Rc), with ***lazy*** initialization, whose
+/// *default* initializer takes a context argument of type `A`
+///
+/// Unlike the *default* initializer of a [`LazyRc<T>`](crate::LazyRc), which
+/// must capture all of its context when the instance is created, the
+/// initializer of a `LazyRcWith<T, A>` receives the context on first access,
+/// via [`or_init_with_arg()`](LazyWith::or_init_with_arg). The initializer
+/// does **not** need to be `Send` or `Sync`:
+///
+/// ```
+/// use lazy_rc::LazyRcWith;
+/// use std::io::Error;
+/// use std::rc::Rc;
+///
+/// struct Config { name: String }
+/// struct Greeter { greeting: String }
+///
+/// let punctuation = Rc::new(String::from("!"));
+/// let greeter: LazyRcWith<Greeter, Config> = LazyRcWith::new(move |config: &Config| {
+///     Ok::<_, Error>(Greeter { greeting: format!("Hello, {}{}", config.name, punctuation) })
+/// });
+///
+/// let config = Config { name: String::from("World") };
+/// assert_eq!(greeter.or_init_with_arg(&config).unwrap().greeting, "Hello, World!");
+/// ```
+pub type LazyRcWith<T, A, E = IoError> = LazyWith<T, A, E, RcFamily, RefCellFamily>;
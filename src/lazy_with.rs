@@ -0,0 +1,89 @@
+/*
+ * lazy_rc - Rc<T> and Arc<T> with *lazy* initialization
+ * This is free and unencumbered software released into the public domain.
+ */
+use std::fmt::Debug;
+
+use crate::{Lazy, State};
+use crate::family::{Captured, LockFamily, PointerFamily, Threading};
+
+type WithFn<T, A, E, L> = <<L as LockFamily>::Threading as Threading>::Init<A, Result<T, E>>;
+
+/// A reference-counting pointer with ***lazy*** initialization, whose
+/// *default* initializer takes a context argument of type `A`, generic over
+/// the [`PointerFamily`] `P` and the [`LockFamily`] `L`
+///
+/// Unlike the *default* initializer of a [`Lazy<T, P, L>`], which must
+/// capture all of its context when the instance is created, the initializer
+/// of a `LazyWith<T, A, E, P, L>` receives the context on first access, via
+/// [`or_init_with_arg()`](Self::or_init_with_arg). The initializer must be
+/// `Send` and `Sync`, if and only if the [threading model](Threading) of `L`
+/// is thread-safe.
+///
+/// Usually, this type is used via one of its aliases,
+/// [`LazyArcWith<T, A>`](crate::LazyArcWith) or
+/// [`LazyRcWith<T, A>`](crate::LazyRcWith).
+pub struct LazyWith<T, A: ?Sized, E, P: PointerFamily, L: LockFamily> {
+    lazy: Lazy<T, P, L>,
+    init_fn: Box<WithFn<T, A, E, L>>,
+}
+
+impl<T, A: ?Sized, E, P: PointerFamily, L: LockFamily> LazyWith<T, A, E, P, L> {
+    /// Create a new instance that is initially *empty* and whose *default*
+    /// initializer is `init_fn()`.
+    pub fn new<U>(init_fn: U) -> Self
+    where
+        U: Fn(&A) -> Result<T, E> + 'static,
+        Captured<L, U>: Send + Sync,
+    {
+        Self {
+            lazy: Lazy::new(),
+            init_fn: L::Threading::box_init(init_fn, |init, arg| init(arg)),
+        }
+    }
+
+    /// Returns `true`, if and only if the "inner" value is initialized.
+    pub fn is_initialized(&self) -> bool {
+        self.lazy.is_initialized()
+    }
+
+    /// Returns the current [state](State) of this instance.
+    pub fn state(&self) -> State {
+        self.lazy.state()
+    }
+
+    /// Returns a pointer to the existing "inner" value, or tries to initialize
+    /// the value right now, passing `arg` to the *default* initializer.
+    ///
+    /// If and only if the "inner" value is **not** initialized yet, the
+    /// *default* initializer is called with `arg` to create the value. If the
+    /// *default* initializer fails, the error is passed through and the
+    /// "inner" value remains in the *uninitialized* state for now. Once the
+    /// "inner" value is initialized, `arg` is ignored by subsequent calls.
+    pub fn or_init_with_arg(&self, arg: &A) -> Result<P::Pointer<T>, E> {
+        self.lazy.or_try_init_with(|| (self.init_fn)(arg))
+    }
+
+    /// Returns a pointer to the "inner" value, if already initialized.
+    pub fn value(&self) -> Option<P::Pointer<T>> {
+        self.lazy.value()
+    }
+
+    /// Resets the "inner" value to the *uninitialized* state, see
+    /// [`Lazy::reset()`].
+    pub fn reset(&self) -> Option<P::Pointer<T>> {
+        self.lazy.reset()
+    }
+
+    /// Returns the underlying [`Lazy<T, P, L>`], e.g. in order to
+    /// [subscribe](Lazy::subscribe) to changes.
+    pub fn lazy(&self) -> &Lazy<T, P, L> {
+        &self.lazy
+    }
+}
+
+impl<T, A: ?Sized, E, P: PointerFamily, L: LockFamily> Debug for LazyWith<T, A, E, P, L> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}With {{ state: {:?} }}", P::LAZY_NAME, self.lazy.state())
+    }
+}
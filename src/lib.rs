@@ -16,12 +16,171 @@
 //! `LazyRc<T>` is *single-threaded*, because so is `Rc<T>`. Therefore, an
 //! `LazyRc<T>` instance can **not** be shared by multiple threads, and you can
 //! **not** use `LazyRc<T>` for **`static`** variables. However, it ***can***
-//! be used for [`thread_local!`](std::thread_local) variables.
+//! be used for [`thread_local!`](std::thread_local) variables. Within a
+//! single-threaded executor, a `LazyRc<T>` can be initialized *asynchronously*
+//! via [`LazyRc::or_try_init_with_async()`], which accepts `!Send` futures.
 //! 
 //! `LazyArc<T>` is *thread-safe*, because so is `Arc<T>`. Therefore, an
 //! `LazyArc<T>` instance can be shared by multiple threads, and you can even
 //! use `LazyArc<T>` for *global* **`static`** variables.
 //! 
+//! More specifically, `LazyArc<T>` is [`Send`] and [`Sync`] if and only if `T`
+//! is `Send` and `Sync`, exactly like `Arc<T>`. This is why the *default*
+//! initializer of a `LazyArc<T>` must be `Send` and `Sync`, whereas the
+//! *default* initializer of a `LazyRc<T>` may capture `!Send` values.
+//! 
+//! # Pointer and Lock Families
+//! 
+//! Both, `LazyRc<T>` and `LazyArc<T>`, are aliases for the generic
+//! [**`Lazy<T, P, L>`**](Lazy), which is parameterized by a
+//! [`PointerFamily`] (e.g. [`ArcFamily`] or [`RcFamily`]) and a
+//! [`LockFamily`] (e.g. [`RwLockFamily`], [`MutexFamily`] or
+//! [`RefCellFamily`]). Third-party pointer types, such as `triomphe::Arc<T>`,
+//! or lock types, such as the ones from `parking_lot`, can be plugged in by
+//! implementing these traits for a marker type. Each lock family declares its
+//! [`Threading`] model, i.e. whether it is [`ThreadSafe`] or
+//! [`SingleThreaded`].
+//! 
+//! With the `parking_lot` feature enabled, `LazyArc<T>` uses a
+//! `parking_lot::RwLock` instead of the `std` one, see [`LazyArc`] for the
+//! differences regarding poisoning and writer starvation.
+//! 
+//! # Atomic Replacement
+//! 
+//! With the `arc-swap` feature enabled, [**`LazyArcSwap<T>`**](LazyArcSwap)
+//! provides a variant of `LazyArc<T>` whose "inner" value can be replaced
+//! *atomically* at any time, e.g. to reload a configuration at runtime. Readers
+//! of a `LazyArcSwap<T>` **never** block on a lock.
+//! 
+//! # Configuration
+//! 
+//! Ready-made *default* initializers are available for the common case of a
+//! `LazyArc<T>` that holds a configuration value, see
+//! [`LazyArc::from_env()`] and [`LazyArc::from_file()`]. With the `json` or
+//! `toml` feature enabled, a configuration file can also be deserialized
+//! directly, see `LazyArc::from_json_file()` and `LazyArc::from_toml_file()`.
+//! 
+//! If the configuration file may be edited while the program is running, use
+//! [**`LazyFileArc<T>`**](LazyFileArc), which *reloads* the "inner" value
+//! whenever the file has changed.
+//! 
+//! # Warm Start
+//! 
+//! With the `persist` feature enabled, the result of an *expensive* initializer
+//! can be cached on disk via `WarmCache`, so that subsequent process starts
+//! load the cached value instead of recomputing it, see
+//! `LazyArc::with_warm_cache()`.
+//! 
+//! # Context Arguments
+//! 
+//! If the initializer needs some context that is only available at runtime,
+//! e.g. a configuration object, use [**`LazyArcWith<T, A>`**](LazyArcWith) or
+//! [**`LazyRcWith<T, A>`**](LazyRcWith), whose *default* initializer receives
+//! the context argument of type `A` on first access. Both are aliases of the
+//! generic [`LazyWith<T, A, E, P, L>`](LazyWith).
+//! 
+//! # Idle Eviction
+//! 
+//! With the `idle-timeout` feature enabled, a value that is only needed in
+//! bursts can be dropped in between, by setting an *idle timeout* via
+//! `LazyArc::set_idle_timeout()`. Once the "inner" value has **not** been
+//! accessed for longer than the timeout, it is evicted, so the next access
+//! runs the *default* initializer again. This is checked on access, or
+//! periodically by an optional sweeper thread, see
+//! `LazyArc::spawn_idle_sweeper()`.
+//! 
+//! # Memory Budget
+//! 
+//! With the `memory-budget` feature enabled, many re-initializable caches
+//! can share a [`MemoryBudget`]. Each instance is registered with a function
+//! that estimates the size of its "inner" value, and the *least recently
+//! used* instances are reset, whenever the total estimated usage exceeds the
+//! limit. Only instances that have a *default* initializer are reset, so
+//! that they can be rebuilt on the next access.
+//! 
+//! # Interoperability
+//! 
+//! A `LazyArc<T>` can be converted from and into a
+//! [`OnceLock<Arc<T>>`](std::sync::OnceLock), see
+//! [`LazyArc::into_once_lock()`], and a `LazyRc<T>` can be converted from and
+//! into a [`OnceCell<Rc<T>>`](std::cell::OnceCell). Furthermore, a
+//! [`LazyLock<Arc<T>>`](std::sync::LazyLock) or a
+//! [`LazyCell<Rc<T>>`](std::cell::LazyCell) can be converted into a
+//! `LazyArc<T>` or a `LazyRc<T>`, respectively. With the `once_cell` feature
+//! enabled, the same conversions are available for the `OnceCell` and `Lazy`
+//! types of the `once_cell` crate. The [**`LazyInit<T>`**](LazyInit) trait
+//! allows generic code to accept any of the `OnceCell`-like lazy cells.
+//! 
+//! # Memoization
+//! 
+//! [**`LazyFn<A, R>`**](LazyFn) and [**`LazyRcFn<A, R>`**](LazyRcFn) wrap a
+//! function and compute its result *lazily*, once per distinct argument, e.g.
+//! to create per-locale formatters or per-schema validators on demand. The
+//! number of cached results can be bounded, in which case the *least recently
+//! used* result is evicted.
+//! 
+//! # Fork Safety
+//! 
+//! With the `fork` feature enabled (Unix only), the behavior of a **`static`**
+//! instance in the *child* process after a `fork()` can be chosen via
+//! `LazyArc::set_fork_policy()`: the child either keeps the "inner" value,
+//! resets it, or re-initializes it on first access, see [`ForkPolicy`]. In any
+//! case, a pending initialization completes *before* the process is forked,
+//! so the child never inherits a lock that is held by another thread.
+//! 
+//! # Statistics
+//! 
+//! With the `stats` feature enabled, every `LazyRc<T>` and `LazyArc<T>`
+//! instance records the number of accesses, initializations, failed attempts
+//! and resets, as well as the time spent in initializers and waiting for the
+//! lock, see `LazyArc::stats()`. If the feature is disabled, **no** counters
+//! are kept at all.
+//! 
+//! # Shutdown
+//! 
+//! A **`static`** instance is *never* dropped, so the `Drop` impl of its
+//! "inner" value, e.g. a database pool or a log flusher, never runs. With the
+//! `shutdown` feature enabled, a `static` instance can be registered via
+//! `LazyArc::register_shutdown()`. Calling [`shutdown()`] at the end of the
+//! program then resets all registered instances in *reverse* initialization
+//! order, so that dependents are dropped before their dependencies, and
+//! reports the values that are still referenced elsewhere.
+//! 
+//! # Testing
+//! 
+//! With the `test-util` feature enabled, the "inner" value of a `LazyArc<T>`
+//! can be *temporarily* overridden via [`LazyArc::override_scoped()`], which
+//! makes code that accesses a global **`static`** instance testable. Together
+//! with the `idle-timeout` feature, the clock of the *idle timeout* can be
+//! advanced via `LazyArc::advance_idle_clock()`, so that idle eviction can be
+//! tested without sleeping. Enable this feature for your `[dev-dependencies]`
+//! only!
+//! 
+//! # Unsized Types
+//! 
+//! The "inner" value may be *unsized*, e.g. `LazyArc<dyn Trait>`,
+//! `LazyArc<str>` or `LazyArc<[T]>`. In this case, use the initializers that
+//! return a pointer, such as [`LazyArc::or_init_with_arc()`], instead of the
+//! initializers that return the value itself:
+//! 
+//! ```
+//! use lazy_rc::LazyArc;
+//! use std::sync::Arc;
+//! 
+//! trait Backend { fn name(&self) -> &str; }
+//! struct Memory;
+//! impl Backend for Memory { fn name(&self) -> &str { "memory" } }
+//! 
+//! static BACKEND: LazyArc<dyn Backend + Send + Sync> = LazyArc::empty();
+//! static GREETING: LazyArc<str> = LazyArc::empty();
+//! 
+//! let backend = BACKEND.or_init_with_arc(|| -> Arc<dyn Backend + Send + Sync> { Arc::new(Memory) });
+//! assert_eq!(backend.name(), "memory");
+//! 
+//! let greeting = GREETING.or_init_with_arc(|| String::from("Hello!"));
+//! assert_eq!(&*greeting, "Hello!");
+//! ```
+//! 
 //! # Const Warning
 //! 
 //! Do **not** use `LazyRc<T>` or `LazyArc<T>` as a **`const`** value! That is
@@ -33,11 +192,13 @@
 //! 
 //! ```
 //! use lazy_rc::{LazyRc, LazyArc};
+//! use std::io::Result;
+//! use std::rc::Rc;
 //! 
 //! static GLOBAL_INSTANCE: LazyArc<MyStruct> = LazyArc::empty();
 //! 
 //! thread_local! {
-//!     static THREAD_INSTANCE: LazyRc<MyStruct>  = LazyRc::empty();
+//!     static THREAD_INSTANCE: LazyRc<MyStruct> = const { LazyRc::empty() };
 //! }
 //! 
 //! struct MyStruct {
@@ -47,6 +208,7 @@
 //! impl MyStruct {
 //!     fn new() -> Result<Self> {
 //!         /* ... */
+//! #       Ok(Self { })
 //!     }
 //! 
 //!     /// Returns a thread-local instance that will be created on first access.
@@ -57,11 +219,67 @@
 //! }
 //! ```
 
+#[cfg(feature = "memory-budget")]
+mod budget;
+mod cancellation;
+mod family;
+#[cfg(all(unix, feature = "fork"))]
+mod fork;
+#[cfg(feature = "idle-timeout")]
+mod idle;
+mod lazy;
 mod lazy_arc;
+#[cfg(feature = "arc-swap")]
+mod lazy_arc_swap;
+mod lazy_arc_with;
+mod lazy_file_arc;
+mod lazy_fn;
+mod lazy_init;
 mod lazy_rc;
+mod lazy_rc_fn;
+mod lazy_rc_with;
+mod lazy_with;
+#[cfg(feature = "test-util")]
+mod overrides;
+#[cfg(feature = "shutdown")]
+mod shutdown;
+mod sources;
+mod state;
+mod stats;
+mod subscription;
+#[cfg(feature = "persist")]
+mod warm_cache;
 
 pub(crate) mod utils;
 
-pub use lazy_arc::LazyArc;
-pub use lazy_rc::LazyRc;
+#[cfg(feature = "memory-budget")]
+pub use budget::{BudgetEntry, MemoryBudget};
+pub use cancellation::CancellationToken;
+pub use family::{ArcFamily, CyclicFamily, LockFamily, MutexFamily, PointerFamily, RcFamily, RefCellFamily, RwLockFamily, SingleThreaded, ThreadSafe, Threading};
+#[cfg(feature = "parking_lot")]
+pub use family::ParkingLotFamily;
+#[cfg(all(unix, feature = "fork"))]
+pub use fork::ForkPolicy;
+pub use lazy::{Handle, Initialized, Lazy};
+#[cfg(feature = "test-util")]
+pub use lazy::OverrideGuard;
+pub use lazy_arc::{ArcHandle, LazyArc};
+#[cfg(feature = "arc-swap")]
+pub use lazy_arc_swap::LazyArcSwap;
+pub use lazy_arc_with::LazyArcWith;
+pub use lazy_file_arc::LazyFileArc;
+pub use lazy_fn::{CacheStats, LazyFn};
+pub use lazy_init::LazyInit;
+pub use lazy_rc::{LazyRc, RcHandle};
+pub use lazy_rc_fn::LazyRcFn;
+pub use lazy_rc_with::LazyRcWith;
+pub use lazy_with::LazyWith;
+#[cfg(feature = "shutdown")]
+pub use shutdown::{shutdown, ShutdownEntry, ShutdownOutcome, ShutdownReport};
+pub use state::State;
+#[cfg(feature = "stats")]
+pub use stats::Stats;
+pub use subscription::{Recv, Subscription};
 pub use utils::InitError;
+#[cfg(feature = "persist")]
+pub use warm_cache::WarmCache;
@@ -0,0 +1,69 @@
+/*
+ * lazy_rc - Rc<T> and Arc<T> with *lazy* initialization
+ * This is free and unencumbered software released into the public domain.
+ */
+use std::sync::{Mutex, MutexGuard};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread::{self, ThreadId};
+use std::time::SystemTime;
+
+use crate::family::PointerFamily;
+
+/// The *thread-local* overrides of a [`Lazy<T, P, L>`](crate::Lazy).
+pub(crate) struct Overrides<T: ?Sized, P: PointerFamily> {
+    count: AtomicUsize,
+    entries: Mutex<Vec<Entry<T, P>>>,
+}
+
+/// An override, with the thread that owns it and the time it was installed.
+type Entry<T, P> = (ThreadId, <P as PointerFamily>::Pointer<T>, SystemTime);
+
+impl<T: ?Sized, P: PointerFamily> Overrides<T, P> {
+    pub const fn new() -> Self {
+        Self {
+            count: AtomicUsize::new(0),
+            entries: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Returns the innermost override of the current thread, if any.
+    pub fn current(&self) -> Option<P::Pointer<T>> {
+        self.find(|(_, value, _)| value.clone())
+    }
+
+    /// Returns the time when the innermost override of the current thread
+    /// was installed, if any.
+    pub fn installed_at(&self) -> Option<SystemTime> {
+        self.find(|(_, _, at)| *at)
+    }
+
+    /// Installs a new override for the current thread.
+    pub fn push(&self, value: P::Pointer<T>) {
+        self.lock().push((thread::current().id(), value, SystemTime::now()));
+        self.count.fetch_add(1, Ordering::AcqRel);
+    }
+
+    /// Removes a previously installed override.
+    pub fn remove(&self, value: &P::Pointer<T>) {
+        let mut entries = self.lock();
+        if let Some(position) = entries.iter().rposition(|(_, existing, _)| P::ptr_eq(existing, value)) {
+            entries.remove(position);
+            self.count.fetch_sub(1, Ordering::AcqRel);
+        }
+    }
+
+    fn find<R, F>(&self, map_fn: F) -> Option<R>
+    where
+        F: FnOnce(&Entry<T, P>) -> R,
+    {
+        if self.count.load(Ordering::Acquire) == 0 {
+            return None;
+        }
+        let thread_id = thread::current().id();
+        self.lock().iter().rev().find(|(owner, _, _)| *owner == thread_id).map(map_fn)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Vec<Entry<T, P>>> {
+        self.entries.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
@@ -0,0 +1,148 @@
+/*
+ * lazy_rc - Rc<T> and Arc<T> with *lazy* initialization
+ * This is free and unencumbered software released into the public domain.
+ */
+use std::cmp::Reverse;
+use std::ptr;
+use std::sync::{mpsc::{self, RecvTimeoutError}, Mutex, MutexGuard};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::thread;
+use std::time::Duration;
+
+/// The outcome of shutting down a single registered instance, see
+/// [`shutdown()`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShutdownOutcome {
+    /// The "inner" value has been reset and dropped.
+    Dropped,
+    /// The "inner" value has been reset, but it was **not** dropped, because
+    /// it is still referenced elsewhere.
+    StillReferenced,
+    /// The "inner" value could **not** be reset and dropped within the
+    /// timeout, e.g. because a pending initialization or the `Drop` impl of
+    /// the value has blocked. The reset still proceeds in the background.
+    TimedOut,
+    /// The "inner" value could **not** be reset, because a panic occurred,
+    /// e.g. in the `Drop` impl of the value.
+    Panicked,
+}
+
+/// The shutdown of a single registered instance, see [`ShutdownReport`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ShutdownEntry {
+    /// The type name of the "inner" value.
+    pub type_name: &'static str,
+    /// The outcome of shutting down the instance.
+    pub outcome: ShutdownOutcome,
+}
+
+/// The report that is returned by [`shutdown()`]
+///
+/// The entries appear in the order in which the instances were shut down,
+/// i.e. in *reverse* initialization order. Registered instances that were
+/// **not** initialized do **not** appear at all.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ShutdownReport {
+    /// The shutdown of each initialized instance.
+    pub entries: Vec<ShutdownEntry>,
+}
+
+/// An instance that can be registered for [`shutdown()`].
+pub(crate) trait Shutdown: Sync {
+    /// Returns the registration of this instance.
+    fn registration(&self) -> &Registration;
+
+    /// Returns the type name of the "inner" value.
+    fn type_name(&self) -> &'static str;
+
+    /// Resets the "inner" value, returning `None` if it was **not** initialized.
+    fn shutdown(&self) -> Option<ShutdownOutcome>;
+}
+
+/// The shutdown bookkeeping of a [`Lazy<T, P, L>`](crate::Lazy).
+pub(crate) struct Registration {
+    registered: AtomicBool,
+    sequence: AtomicU64,
+}
+
+static REGISTRY: Mutex<Vec<(&'static dyn Shutdown, Duration)>> = Mutex::new(Vec::new());
+static SEQUENCE: AtomicU64 = AtomicU64::new(1);
+
+impl ShutdownReport {
+    /// Returns `true`, if and only if the "inner" values of **all** instances
+    /// have been dropped.
+    pub fn is_clean(&self) -> bool {
+        self.entries.iter().all(|entry| entry.outcome == ShutdownOutcome::Dropped)
+    }
+
+    /// Returns the entries whose "inner" values are still referenced elsewhere.
+    pub fn still_referenced(&self) -> impl Iterator<Item = &ShutdownEntry> {
+        self.entries.iter().filter(|entry| entry.outcome == ShutdownOutcome::StillReferenced)
+    }
+}
+
+impl Registration {
+    pub const fn new() -> Self {
+        Self {
+            registered: AtomicBool::new(false),
+            sequence: AtomicU64::new(0),
+        }
+    }
+
+    /// Records that the "inner" value has been initialized just now.
+    pub fn initialized(&self) {
+        self.sequence.store(SEQUENCE.fetch_add(1, Ordering::Relaxed), Ordering::Release);
+    }
+
+    /// Registers `instance` for [`shutdown()`], or updates its `timeout`, if
+    /// it is registered already.
+    pub fn register(instance: &'static dyn Shutdown, timeout: Duration) {
+        let mut registry = lock();
+        if instance.registration().registered.swap(true, Ordering::AcqRel) {
+            if let Some(entry) = registry.iter_mut().find(|(existing, _)| ptr::addr_eq(*existing, instance)) {
+                entry.1 = timeout;
+            }
+        } else {
+            registry.push((instance, timeout));
+        }
+    }
+}
+
+/// Shuts down all registered instances, by resetting their "inner" values in
+/// *reverse* initialization order, so that dependents are dropped before
+/// their dependencies.
+///
+/// Instances are registered via
+/// [`Lazy::register_shutdown()`](crate::Lazy::register_shutdown). Each
+/// instance is reset on a separate thread, waiting at most for the timeout
+/// that was specified at registration. The "inner" value is dropped, unless
+/// it is still referenced elsewhere; this is recorded in the returned report.
+///
+/// The instances remain registered, so they may be initialized and shut down
+/// again.
+///
+/// This function is only available with the `shutdown` feature enabled.
+pub fn shutdown() -> ShutdownReport {
+    let mut instances = lock().clone();
+    instances.sort_by_key(|(instance, _)| Reverse(instance.registration().sequence.load(Ordering::Acquire)));
+    let entries = instances.into_iter()
+        .filter_map(|(instance, timeout)| {
+            shutdown_instance(instance, timeout).map(|outcome| ShutdownEntry { type_name: instance.type_name(), outcome })
+        })
+        .collect();
+    ShutdownReport { entries }
+}
+
+fn shutdown_instance(instance: &'static dyn Shutdown, timeout: Duration) -> Option<ShutdownOutcome> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || sender.send(instance.shutdown()));
+    match receiver.recv_timeout(timeout) {
+        Ok(outcome) => outcome,
+        Err(RecvTimeoutError::Timeout) => Some(ShutdownOutcome::TimedOut),
+        Err(RecvTimeoutError::Disconnected) => Some(ShutdownOutcome::Panicked),
+    }
+}
+
+fn lock() -> MutexGuard<'static, Vec<(&'static dyn Shutdown, Duration)>> {
+    REGISTRY.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
@@ -0,0 +1,56 @@
+/*
+ * lazy_rc - Rc<T> and Arc<T> with *lazy* initialization
+ * This is free and unencumbered software released into the public domain.
+ */
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::InitError;
+use crate::utils::BoxError;
+
+/// Creates an initializer that parses the environment variable `name`.
+pub fn env_var<T, E, F>(name: String, parse_fn: F) -> impl Fn() -> Result<T, InitError> + Send + Sync + 'static
+where
+    E: Into<BoxError>,
+    F: Fn(&str) -> Result<T, E> + Send + Sync + 'static,
+{
+    move || {
+        let value = env::var(&name)
+            .map_err(|error| InitError::Env { name: name.clone(), error: error.into() })?;
+        parse_fn(&value)
+            .map_err(|error| InitError::Env { name: name.clone(), error: error.into() })
+    }
+}
+
+/// Creates an initializer that parses the file at `path`.
+pub fn file<T, E, F>(path: PathBuf, parse_fn: F) -> impl Fn() -> Result<T, InitError> + Send + Sync + 'static
+where
+    E: Into<BoxError>,
+    F: Fn(&str) -> Result<T, E> + Send + Sync + 'static,
+{
+    move || {
+        let content = fs::read_to_string(&path)
+            .map_err(|error| InitError::File { path: path.clone(), error: error.into() })?;
+        parse_fn(&content)
+            .map_err(|error| InitError::File { path: path.clone(), error: error.into() })
+    }
+}
+
+/// Creates an initializer that deserializes the JSON file at `path`.
+#[cfg(feature = "json")]
+pub fn json_file<T>(path: PathBuf) -> impl Fn() -> Result<T, InitError> + Send + Sync + 'static
+where
+    T: serde::de::DeserializeOwned,
+{
+    file(path, |content| serde_json::from_str(content))
+}
+
+/// Creates an initializer that deserializes the TOML file at `path`.
+#[cfg(feature = "toml")]
+pub fn toml_file<T>(path: PathBuf) -> impl Fn() -> Result<T, InitError> + Send + Sync + 'static
+where
+    T: serde::de::DeserializeOwned,
+{
+    file(path, |content| toml::from_str(content))
+}
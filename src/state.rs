@@ -0,0 +1,322 @@
+/*
+ * lazy_rc - Rc<T> and Arc<T> with *lazy* initialization
+ * This is free and unencumbered software released into the public domain.
+ */
+use std::any::type_name;
+use std::cell::RefCell;
+use std::convert::Infallible;
+use std::fmt::Debug;
+use std::future::Future;
+use std::mem;
+use std::pin::Pin;
+use std::sync::{Condvar, Mutex, MutexGuard};
+#[cfg(all(unix, feature = "fork"))]
+use std::sync::TryLockError;
+use std::task::{Context, Poll, Waker};
+use std::thread::{self, ThreadId};
+use std::time::{Instant, SystemTime};
+
+/// The current state of a [`LazyArc<T>`](crate::LazyArc) or
+/// [`LazyRc<T>`](crate::LazyRc) instance
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum State {
+    /// The "inner" value is **not** initialized, and **no** initialization
+    /// attempt has failed since the instance was created or last initialized.
+    Uninitialized,
+    /// The "inner" value is being initialized right now by `thread`, which
+    /// has started the initializer at `since`.
+    Initializing { thread: ThreadId, since: Instant },
+    /// The "inner" value is initialized; it was set at `at`.
+    Initialized { at: SystemTime },
+    /// The "inner" value is **not** initialized, because the most recent
+    /// initialization attempt has failed; `error` describes the failure. For
+    /// an error of an arbitrary type, only the *name* of that type is known.
+    Failed { error: String },
+}
+
+/// The bookkeeping that is required to determine the current [`State`].
+///
+/// This type is `pub` (but **not** exported), because it appears in the
+/// sealed [`Threading`](crate::Threading) trait; the same applies below.
+#[derive(Default)]
+pub struct Status {
+    initializing: Option<(ThreadId, Instant)>,
+    initialized_at: Option<SystemTime>,
+    failure: Option<String>,
+    wakers: Vec<Waker>,
+}
+
+/// The thread-safe [`Status`] of a [`Lazy<T, P, L>`](crate::Lazy), which
+/// allows waiting for a pending initialization to complete.
+pub struct SyncStatus {
+    status: Mutex<Status>,
+    signal: Condvar,
+}
+
+/// A container that holds the [`Status`] and that can be updated, i.e. a
+/// [`SyncStatus`], or a `RefCell<Status>` for single-threaded instances.
+pub trait StatusCell: Sized {
+    /// Inspects (or updates) the status, *without* notifying anybody.
+    fn inspect<R, F: FnOnce(&mut Status) -> R>(&self, inspect_fn: F) -> R;
+
+    /// Wakes up all threads that are waiting for the status to change.
+    fn notify(&self);
+
+    /// Claims the *initializing* state for the current thread, waiting while
+    /// *another* thread is initializing the "inner" value, but at most until
+    /// `deadline`. Returns `None`, if the deadline has elapsed.
+    ///
+    /// The claim is released when the returned [`Tracking`] is dropped. If
+    /// the current thread (or a pending *asynchronous* initialization on the
+    /// current thread) holds the claim already, it is **not** claimed again.
+    fn claim(&self, deadline: Option<Instant>) -> Option<Tracking<'_, Self>>;
+
+    /// Updates the status, then notifies all waiting threads and tasks.
+    fn with<R, F: FnOnce(&mut Status) -> R>(&self, update_fn: F) -> R {
+        let (result, wakers) = self.inspect(|status| (update_fn(status), mem::take(&mut status.wakers)));
+        self.notify();
+        wakers.into_iter().for_each(Waker::wake);
+        result
+    }
+
+    /// Returns a future that claims the *initializing* state for the current
+    /// task, as soon as **no** other task or thread is initializing the
+    /// "inner" value. The claim is released when the returned [`Tracking`]
+    /// is dropped, which wakes up the next waiting task.
+    fn claim_async(&self) -> Claim<'_, Self> {
+        Claim { cell: self }
+    }
+}
+
+/// Clears the *initializing* state when dropped, e.g. if the initializer panics.
+/// 
+/// Only a `Tracking` that has *set* the initializing state clears it, so that
+/// a synchronous initializer never releases the claim of a pending
+/// *asynchronous* initialization.
+pub struct Tracking<'a, C: StatusCell> {
+    cell: &'a C,
+    owned: bool,
+}
+
+/// A future that claims the *initializing* state, see [`StatusCell::claim_async()`].
+pub struct Claim<'a, C: StatusCell> {
+    cell: &'a C,
+}
+
+impl Status {
+    pub const fn new() -> Self {
+        Self {
+            initializing: None,
+            initialized_at: None,
+            failure: None,
+            wakers: Vec::new(),
+        }
+    }
+
+    /// Records that the "inner" value has been set (`true`) or reset (`false`).
+    pub fn changed(&mut self, initialized: bool) {
+        self.initialized_at = initialized.then(SystemTime::now);
+        if initialized {
+            self.failure = None;
+        }
+    }
+
+    pub fn is_initialized(&self) -> bool {
+        self.initialized_at.is_some()
+    }
+
+    /// Returns `true`, if the *current* thread is initializing the "inner" value.
+    #[cfg(all(unix, feature = "fork"))]
+    pub fn is_initializing_here(&self) -> bool {
+        self.initializing.is_some_and(|(thread, _)| thread == thread::current().id())
+    }
+
+    /// Returns `true`, if *another* thread is initializing the "inner" value.
+    pub fn is_initializing_elsewhere(&self) -> bool {
+        self.initializing.is_some_and(|(thread, _)| thread != thread::current().id())
+    }
+
+    /// Records that the "inner" value has been reset in a *forked* child
+    /// process, in which **no** other threads or tasks exist anymore.
+    #[cfg(all(unix, feature = "fork"))]
+    pub fn forked(&mut self) {
+        self.changed(false);
+        self.initializing = None;
+        mem::forget(mem::take(&mut self.wakers));
+    }
+
+    pub fn state(&self) -> State {
+        if let Some((thread, since)) = self.initializing {
+            return State::Initializing { thread, since };
+        }
+        match (self.initialized_at, &self.failure) {
+            (Some(at), _) => State::Initialized { at },
+            (None, Some(error)) => State::Failed { error: error.clone() },
+            (None, None) => State::Uninitialized,
+        }
+    }
+
+    /// Sets the *initializing* state for the current thread, unless it is set
+    /// already. Returns `true`, if it was set just now.
+    fn claim(&mut self) -> bool {
+        match self.initializing {
+            Some(_) => false,
+            None => {
+                self.initializing = Some((thread::current().id(), Instant::now()));
+                true
+            },
+        }
+    }
+}
+
+impl SyncStatus {
+    pub const fn new() -> Self {
+        Self {
+            status: Mutex::new(Status::new()),
+            signal: Condvar::new(),
+        }
+    }
+
+    /// Waits while *another* thread is initializing the "inner" value,
+    /// blocking the current thread until at most `deadline`. Returns the
+    /// locked status, or `None`, if the deadline has elapsed while the
+    /// initialization was still pending.
+    pub fn wait(&self, deadline: Option<Instant>) -> Option<MutexGuard<'_, Status>> {
+        let mut status = self.lock();
+        while status.is_initializing_elsewhere() {
+            status = match deadline {
+                Some(deadline) => {
+                    let remaining = deadline.saturating_duration_since(Instant::now());
+                    if remaining.is_zero() {
+                        return None;
+                    }
+                    self.signal.wait_timeout(status, remaining)
+                        .map(|(status, _)| status)
+                        .unwrap_or_else(|poisoned| poisoned.into_inner().0)
+                },
+                None => self.signal.wait(status).unwrap_or_else(|poisoned| poisoned.into_inner()),
+            };
+        }
+        Some(status)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Status> {
+        self.status.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Locks the status, if that is possible without blocking.
+    #[cfg(all(unix, feature = "fork"))]
+    pub fn try_lock(&self) -> Option<MutexGuard<'_, Status>> {
+        match self.status.try_lock() {
+            Ok(status) => Some(status),
+            Err(TryLockError::Poisoned(poisoned)) => Some(poisoned.into_inner()),
+            Err(TryLockError::WouldBlock) => None,
+        }
+    }
+}
+
+impl StatusCell for SyncStatus {
+    fn inspect<R, F: FnOnce(&mut Status) -> R>(&self, inspect_fn: F) -> R {
+        inspect_fn(&mut self.lock())
+    }
+
+    fn notify(&self) {
+        self.signal.notify_all();
+    }
+
+    fn claim(&self, deadline: Option<Instant>) -> Option<Tracking<'_, Self>> {
+        let owned = self.wait(deadline)?.claim();
+        Some(Tracking { cell: self, owned })
+    }
+}
+
+impl StatusCell for RefCell<Status> {
+    fn inspect<R, F: FnOnce(&mut Status) -> R>(&self, inspect_fn: F) -> R {
+        inspect_fn(&mut self.borrow_mut())
+    }
+
+    fn notify(&self) { }
+
+    fn claim(&self, _deadline: Option<Instant>) -> Option<Tracking<'_, Self>> {
+        // No other thread can access the status, so there is nothing to wait for
+        let owned = self.borrow_mut().claim();
+        Some(Tracking { cell: self, owned })
+    }
+}
+
+impl<'a, C: StatusCell> Future for Claim<'a, C> {
+    type Output = Tracking<'a, C>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Tracking<'a, C>> {
+        let cell = self.cell;
+        cell.inspect(|status| {
+            if status.initializing.is_some() {
+                if !status.wakers.iter().any(|waker| waker.will_wake(cx.waker())) {
+                    status.wakers.push(cx.waker().clone());
+                }
+                return Poll::Pending;
+            }
+            status.claim();
+            Poll::Ready(Tracking { cell, owned: true })
+        })
+    }
+}
+
+impl<C: StatusCell> Tracking<'_, C> {
+    /// Records that the initialization has failed, as described by `description`.
+    pub fn failed(&self, description: String) {
+        self.cell.with(|status| status.failure = Some(description));
+    }
+}
+
+impl<C: StatusCell> Drop for Tracking<'_, C> {
+    fn drop(&mut self) {
+        let owned = self.owned;
+        self.cell.with(|status| {
+            let released = owned && status.initializing.take().is_some();
+            if released && thread::panicking() {
+                status.failure = Some(String::from("The initializer has panicked!"));
+            }
+        })
+    }
+}
+
+/// Calls the initializer `init_fn()`, while tracking the *initializing* state
+/// and recording a failure, which is described by `describe_fn()`.
+pub(crate) fn track<C, U, E, F, D>(cell: &C, init_fn: F, describe_fn: D) -> Result<U, E>
+where
+    C: StatusCell,
+    F: FnOnce() -> Result<U, E>,
+    D: FnOnce(&E) -> String,
+{
+    // A pending asynchronous initialization keeps its claim
+    let owned = cell.with(Status::claim);
+    let tracking = Tracking { cell, owned };
+    let result = init_fn();
+    match &result {
+        Ok(_) => cell.with(|status| status.changed(true)),
+        Err(error) => {
+            let description = describe_fn(error);
+            cell.with(|status| status.failure = Some(description));
+        },
+    }
+    drop(tracking);
+    result
+}
+
+/// Calls the *infailable* initializer `init_fn()`, see [`track()`].
+pub(crate) fn track_infailable<C, U, F>(cell: &C, init_fn: F) -> U
+where
+    C: StatusCell,
+    F: FnOnce() -> U,
+{
+    match track(cell, || Ok::<U, Infallible>(init_fn()), |never| match *never { }) {
+        Ok(value) => value,
+        Err(never) => match never { },
+    }
+}
+
+/// Describes an error of an arbitrary type, which may **not** be displayable.
+pub(crate) fn describe<E>(_error: &E) -> String {
+    format!("The initializer has failed with an error of type `{}`", type_name::<E>())
+}
@@ -0,0 +1,168 @@
+/*
+ * lazy_rc - Rc<T> and Arc<T> with *lazy* initialization
+ * This is free and unencumbered software released into the public domain.
+ */
+use std::convert::Infallible;
+use std::future::Future;
+#[cfg(feature = "stats")]
+use std::sync::atomic::{AtomicU64, Ordering};
+#[cfg(feature = "stats")]
+use std::time::{Duration, Instant};
+
+/// The statistics of a [`LazyArc<T>`](crate::LazyArc) or
+/// [`LazyRc<T>`](crate::LazyRc) instance
+///
+/// This type is only available with the `stats` feature enabled.
+#[cfg(feature = "stats")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Stats {
+    /// The number of accesses, i.e. calls to `or_init()`, `or_try_init()`,
+    /// `or_init_with()`, `value()`, `map()`, etc.
+    pub accesses: u64,
+    /// The number of *successful* initializations.
+    pub initializations: u64,
+    /// The number of *failed* initialization attempts.
+    pub failures: u64,
+    /// The number of times the "inner" value was reset.
+    pub resets: u64,
+    /// The cumulative time spent in initializers, including failed attempts.
+    pub init_time: Duration,
+    /// The maximum time spent in a single initializer call.
+    pub max_init_time: Duration,
+    /// The cumulative time spent waiting for the lock. This is practically
+    /// zero for a `LazyRc<T>`, because a `RefCell<T>` never blocks.
+    pub lock_wait_time: Duration,
+}
+
+/// The thread-safe counters of a [`Lazy<T, P, L>`](crate::Lazy); this is a
+/// zero-sized no-op, unless the `stats` feature is enabled.
+pub(crate) struct Counters {
+    #[cfg(feature = "stats")]
+    accesses: AtomicU64,
+    #[cfg(feature = "stats")]
+    initializations: AtomicU64,
+    #[cfg(feature = "stats")]
+    failures: AtomicU64,
+    #[cfg(feature = "stats")]
+    resets: AtomicU64,
+    #[cfg(feature = "stats")]
+    init_nanos: AtomicU64,
+    #[cfg(feature = "stats")]
+    max_init_nanos: AtomicU64,
+    #[cfg(feature = "stats")]
+    lock_wait_nanos: AtomicU64,
+}
+
+#[cfg(feature = "stats")]
+impl Counters {
+    pub const fn new() -> Self {
+        Self {
+            accesses: AtomicU64::new(0),
+            initializations: AtomicU64::new(0),
+            failures: AtomicU64::new(0),
+            resets: AtomicU64::new(0),
+            init_nanos: AtomicU64::new(0),
+            max_init_nanos: AtomicU64::new(0),
+            lock_wait_nanos: AtomicU64::new(0),
+        }
+    }
+
+    pub fn access(&self) {
+        self.accesses.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn reset(&self) {
+        self.resets.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Acquires a lock via `lock_fn()`, recording the time spent waiting.
+    pub fn lock<G, F: FnOnce() -> G>(&self, lock_fn: F) -> G {
+        let start = Instant::now();
+        let guard = lock_fn();
+        self.lock_wait_nanos.fetch_add(nanos(start.elapsed()), Ordering::Relaxed);
+        guard
+    }
+
+    /// Calls the initializer `init_fn()`, recording its duration and outcome.
+    pub fn try_init<U, E, F: FnOnce() -> Result<U, E>>(&self, init_fn: F) -> Result<U, E> {
+        let start = Instant::now();
+        let result = init_fn();
+        self.record(start, &result);
+        result
+    }
+
+    /// Awaits the initializer future `init_future`, recording its duration
+    /// and outcome, see [`try_init()`](Self::try_init).
+    pub async fn try_init_async<U, E, F: Future<Output = Result<U, E>>>(&self, init_future: F) -> Result<U, E> {
+        let start = Instant::now();
+        let result = init_future.await;
+        self.record(start, &result);
+        result
+    }
+
+    fn record<U, E>(&self, start: Instant, result: &Result<U, E>) {
+        let elapsed = nanos(start.elapsed());
+        self.init_nanos.fetch_add(elapsed, Ordering::Relaxed);
+        self.max_init_nanos.fetch_max(elapsed, Ordering::Relaxed);
+        match result {
+            Ok(_) => self.initializations.fetch_add(1, Ordering::Relaxed),
+            Err(_) => self.failures.fetch_add(1, Ordering::Relaxed),
+        };
+    }
+
+    pub fn snapshot(&self) -> Stats {
+        Stats {
+            accesses: self.accesses.load(Ordering::Relaxed),
+            initializations: self.initializations.load(Ordering::Relaxed),
+            failures: self.failures.load(Ordering::Relaxed),
+            resets: self.resets.load(Ordering::Relaxed),
+            init_time: Duration::from_nanos(self.init_nanos.load(Ordering::Relaxed)),
+            max_init_time: Duration::from_nanos(self.max_init_nanos.load(Ordering::Relaxed)),
+            lock_wait_time: Duration::from_nanos(self.lock_wait_nanos.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+#[cfg(not(feature = "stats"))]
+impl Counters {
+    pub const fn new() -> Self {
+        Self { }
+    }
+
+    #[inline(always)]
+    pub fn access(&self) { }
+
+    #[inline(always)]
+    pub fn reset(&self) { }
+
+    #[inline(always)]
+    pub fn lock<G, F: FnOnce() -> G>(&self, lock_fn: F) -> G {
+        lock_fn()
+    }
+
+    #[inline(always)]
+    pub fn try_init<U, E, F: FnOnce() -> Result<U, E>>(&self, init_fn: F) -> Result<U, E> {
+        init_fn()
+    }
+
+    #[inline(always)]
+    pub async fn try_init_async<U, E, F: Future<Output = Result<U, E>>>(&self, init_future: F) -> Result<U, E> {
+        init_future.await
+    }
+}
+
+impl Counters {
+    /// Calls the *infailable* initializer `init_fn()`, see [`try_init()`](Self::try_init).
+    #[inline(always)]
+    pub fn init<U, F: FnOnce() -> U>(&self, init_fn: F) -> U {
+        match self.try_init(|| Ok::<U, Infallible>(init_fn())) {
+            Ok(value) => value,
+            Err(never) => match never { },
+        }
+    }
+}
+
+#[cfg(feature = "stats")]
+fn nanos(duration: Duration) -> u64 {
+    u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX)
+}
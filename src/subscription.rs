@@ -0,0 +1,225 @@
+/*
+ * lazy_rc - Rc<T> and Arc<T> with *lazy* initialization
+ * This is free and unencumbered software released into the public domain.
+ */
+use std::fmt::Debug;
+use std::future::Future;
+use std::mem;
+use std::pin::Pin;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, Weak};
+use std::sync::mpsc::{RecvError, RecvTimeoutError, TryRecvError};
+use std::task::{Context, Poll, Waker};
+use std::time::{Duration, Instant};
+
+use crate::family::{ArcFamily, PointerFamily, Threading};
+
+pub(crate) type Channels<T, P> = Vec<Weak<Channel<<P as PointerFamily>::Pointer<T>>>>;
+
+/// The list of all active subscriptions of a [`Lazy<T, P, L>`](crate::Lazy),
+/// which is synchronized according to the threading model `H`.
+pub(crate) struct Subscribers<T: ?Sized, P: PointerFamily, H: Threading> {
+    channels: H::Cell<Channels<T, P>>,
+}
+
+pub(crate) struct Channel<V> {
+    queue: Mutex<Queue<V>>,
+    signal: Condvar,
+}
+
+/// The *latest* pending notification, if any, as in a "watch" channel.
+struct Queue<V> {
+    latest: Option<Option<V>>,
+    waker: Option<Waker>,
+    closed: bool,
+}
+
+/// A subscription to the changes of a [`LazyArc<T>`](crate::LazyArc), or of
+/// any other [`Lazy<T, P, L>`](crate::Lazy) whose pointer family is `P`.
+/// 
+/// A subscription is notified every time the "inner" value of the
+/// `LazyArc<T>` is initialized, replaced or reset. Each notification carries
+/// the pointer to the *new* "inner" value, or `None`, if the "inner" value was
+/// reset. Only the *latest* notification is kept pending, i.e. if multiple
+/// changes happen before the notification is received, the subscription only
+/// receives the most recent one. Hence, a slow subscriber never accumulates a
+/// backlog, and it never observes an outdated value.
+/// 
+/// Notifications can be received *blocking*, via [`recv()`](Self::recv), or
+/// *asynchronously*, via [`recv_async()`](Self::recv_async). Once the
+/// originating `LazyArc<T>` has been dropped, the pending notification can
+/// still be received, after that an error is returned.
+pub struct Subscription<T: ?Sized, P: PointerFamily = ArcFamily> {
+    channel: Arc<Channel<P::Pointer<T>>>,
+    initial: Option<P::Pointer<T>>,
+}
+
+/// A future that resolves to the next notification of a [`Subscription<T>`].
+pub struct Recv<'a, T: ?Sized, P: PointerFamily = ArcFamily> {
+    subscription: &'a Subscription<T, P>,
+}
+
+impl<T: ?Sized, P: PointerFamily, H: Threading> Subscribers<T, P, H> {
+    /// Creates a new list, given an *empty* container of type `H::Cell`.
+    pub const fn new(channels: H::Cell<Channels<T, P>>) -> Self {
+        Self { channels }
+    }
+
+    /// Creates a new subscription, must be called while the "inner" value is locked!
+    pub fn subscribe(&self, initial: Option<P::Pointer<T>>) -> Subscription<T, P> {
+        let channel = Arc::new(Channel {
+            queue: Mutex::new(Queue { latest: None, waker: None, closed: false }),
+            signal: Condvar::new(),
+        });
+        H::with_cell(&self.channels, |channels| channels.push(Arc::downgrade(&channel)));
+        Subscription { channel, initial }
+    }
+
+    /// Notifies all subscriptions, must be called while the "inner" value is locked!
+    pub fn publish(&self, value: Option<&P::Pointer<T>>) {
+        H::with_cell(&self.channels, |channels| channels.retain(|channel| match channel.upgrade() {
+            Some(channel) => {
+                channel.push(value.cloned());
+                true
+            },
+            None => false,
+        }))
+    }
+}
+
+impl<T: ?Sized, P: PointerFamily, H: Threading> Drop for Subscribers<T, P, H> {
+    fn drop(&mut self) {
+        let channels = H::with_cell(&self.channels, mem::take);
+        for channel in channels.into_iter().filter_map(|channel| channel.upgrade()) {
+            channel.close();
+        }
+    }
+}
+
+impl<V> Channel<V> {
+    fn push(&self, value: Option<V>) {
+        let mut queue = self.lock();
+        queue.latest = Some(value);
+        self.wake(queue);
+    }
+
+    fn close(&self) {
+        let mut queue = self.lock();
+        queue.closed = true;
+        self.wake(queue);
+    }
+
+    fn wake(&self, mut queue: MutexGuard<'_, Queue<V>>) {
+        let waker = queue.waker.take();
+        drop(queue);
+        self.signal.notify_all();
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Queue<V>> {
+        self.queue.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl<T: ?Sized, P: PointerFamily> Subscription<T, P> {
+    /// Returns the pointer to the "inner" value at the time when this
+    /// subscription was created, or `None`, if it was *uninitialized*.
+    /// 
+    /// Any change that happens *after* this value was observed results in a
+    /// notification; **no** change can be missed in between.
+    pub fn initial(&self) -> Option<&P::Pointer<T>> {
+        self.initial.as_ref()
+    }
+
+    /// Waits for the next notification, blocking the current thread.
+    /// 
+    /// Returns the pointer to the *latest* "inner" value, or `None`, if the
+    /// "inner" value was reset most recently. An error is returned, if **no** more
+    /// notifications can be received, because the `LazyArc<T>` was dropped.
+    pub fn recv(&self) -> Result<Option<P::Pointer<T>>, RecvError> {
+        let mut queue = self.channel.lock();
+        loop {
+            match Self::pop(&mut queue) {
+                Ok(value) => return Ok(value),
+                Err(TryRecvError::Disconnected) => return Err(RecvError),
+                Err(TryRecvError::Empty) => {
+                    queue = self.channel.signal.wait(queue).unwrap_or_else(|poisoned| poisoned.into_inner());
+                },
+            }
+        }
+    }
+
+    /// Waits for the next notification, blocking the current thread for at
+    /// most `timeout`.
+    /// 
+    /// This works like [`recv()`](Self::recv), except that an error is
+    /// returned, if **no** notification was received within `timeout`. A
+    /// `timeout` that is too large to be represented, e.g. `Duration::MAX`,
+    /// waits without a time limit.
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<Option<P::Pointer<T>>, RecvTimeoutError> {
+        let Some(deadline) = Instant::now().checked_add(timeout) else {
+            return self.recv().map_err(|_| RecvTimeoutError::Disconnected);
+        };
+        let mut queue = self.channel.lock();
+        loop {
+            match Self::pop(&mut queue) {
+                Ok(value) => return Ok(value),
+                Err(TryRecvError::Disconnected) => return Err(RecvTimeoutError::Disconnected),
+                Err(TryRecvError::Empty) => {
+                    let remaining = deadline.saturating_duration_since(Instant::now());
+                    if remaining.is_zero() {
+                        return Err(RecvTimeoutError::Timeout);
+                    }
+                    queue = self.channel.signal.wait_timeout(queue, remaining)
+                        .map(|(queue, _)| queue)
+                        .unwrap_or_else(|poisoned| poisoned.into_inner().0);
+                },
+            }
+        }
+    }
+
+    /// Returns the next notification, if one is pending, without blocking.
+    pub fn try_recv(&self) -> Result<Option<P::Pointer<T>>, TryRecvError> {
+        Self::pop(&mut self.channel.lock())
+    }
+
+    /// Returns a future that resolves to the next notification.
+    /// 
+    /// This works like [`recv()`](Self::recv), except that the current
+    /// *task* is suspended, rather than blocking the current thread.
+    pub fn recv_async(&self) -> Recv<'_, T, P> {
+        Recv { subscription: self }
+    }
+
+    fn pop(queue: &mut Queue<P::Pointer<T>>) -> Result<Option<P::Pointer<T>>, TryRecvError> {
+        match queue.latest.take() {
+            Some(value) => Ok(value),
+            None if queue.closed => Err(TryRecvError::Disconnected),
+            None => Err(TryRecvError::Empty),
+        }
+    }
+}
+
+impl<T: ?Sized, P: PointerFamily> Future for Recv<'_, T, P> {
+    type Output = Result<Option<P::Pointer<T>>, RecvError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut queue = self.subscription.channel.lock();
+        match Subscription::<T, P>::pop(&mut queue) {
+            Ok(value) => Poll::Ready(Ok(value)),
+            Err(TryRecvError::Disconnected) => Poll::Ready(Err(RecvError)),
+            Err(TryRecvError::Empty) => {
+                queue.waker = Some(cx.waker().clone());
+                Poll::Pending
+            },
+        }
+    }
+}
+
+impl<T: ?Sized, P: PointerFamily> Debug for Subscription<T, P> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let queue = self.channel.lock();
+        write!(f, "Subscription {{ pending: {:?}, closed: {:?} }}", queue.latest.is_some(), queue.closed)
+    }
+}
@@ -4,28 +4,48 @@
  */
 use std::error::Error;
 use std::fmt::Debug;
-use std::io::{Error as IoError, Result as IoResult};
+use std::io::Error as IoError;
 use std::fmt::Display;
 use std::ops::DerefMut;
+use std::path::PathBuf;
+#[cfg(panic = "unwind")]
+use std::panic::{AssertUnwindSafe, catch_unwind, resume_unwind};
 
-type FnInit<T> = dyn Fn() -> T + Sync;
-type FnInitFailable<T> = dyn Fn() -> IoResult<T> + Sync;
+use crate::CancellationToken;
+
+/// A boxed error of any type, as returned by a parser function.
+pub type BoxError = Box<dyn Error + Send + Sync>;
 
 /// A wrapper that optionally contains a (possibly failable) initializer.
-pub enum DefaultInit<T> {
+/// 
+/// The initializer of type `F` creates a new *pointer*, e.g. `Arc<U>`, so that
+/// the pointed-to type `U` may be unsized. All kinds of initializers share the
+/// same function type, which is `Send` and `Sync`, unless it belongs to a
+/// single-threaded [`LockFamily`](crate::LockFamily).
+pub enum DefaultInit<F: ?Sized> {
     None,
-    Infailable(Box<FnInit<T>>),
-    Failable(Box<FnInitFailable<T>>),
+    Infailable(Box<F>),
+    Failable(Box<F>),
+    Cancellable(Box<F>),
 }
 
 /// An error that indicates that the initialization has failed.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum InitError {
     /// Initialization failed, because **no** default initializer is available!
     NoDefaultInitializer,
     /// The initializer function has failed! The original error is forwarded as
     /// "inner" value of this [`InitError`] variant.
     Failed(IoError),
+    /// The environment variable `name` could **not** be read or parsed! The
+    /// original error is forwarded as `error`.
+    Env { name: String, error: BoxError },
+    /// The file at `path` could **not** be read or parsed! The original error
+    /// is forwarded as `error`.
+    File { path: PathBuf, error: BoxError },
+    /// The initialization did **not** complete before the deadline!
+    Timeout,
 }
 
 pub fn or_init_with<T, F>(mut inner: impl DerefMut<Target = Option<T>>, init_fn: F) -> T
@@ -56,23 +76,96 @@ where
     }
 }
 
-impl<T> Debug for DefaultInit<T> {
+/// Marker payload that is used to unwind out of a *cyclic* initializer.
+#[cfg(panic = "unwind")]
+struct CyclicInitFailed;
+
+/// Creates a new pointer via `new_cyclic()`, but allows `init_fn()` to fail.
+/// 
+/// Because `new_cyclic()` insists on getting a value, a failure is "smuggled"
+/// out of it by unwinding, which lets `new_cyclic()` release the allocation.
+#[cfg(panic = "unwind")]
+pub fn try_new_cyclic<T, W, P, E, N, F>(new_cyclic: N, init_fn: F) -> Result<P, E>
+where
+    N: FnOnce(&mut dyn FnMut(&W) -> T) -> P,
+    F: FnOnce(&W) -> Result<T, E>,
+{
+    let mut init_fn = Some(init_fn);
+    let mut error = None;
+    let result = catch_unwind(AssertUnwindSafe(|| new_cyclic(&mut |weak| {
+        match init_fn.take().expect("Initializer called twice!")(weak) {
+            Ok(value) => value,
+            Err(init_error) => {
+                error = Some(init_error);
+                resume_unwind(Box::new(CyclicInitFailed))
+            },
+        }
+    })));
+    match result {
+        Ok(pointer) => Ok(pointer),
+        Err(payload) => match error {
+            Some(init_error) if payload.is::<CyclicInitFailed>() => Err(init_error),
+            _ => resume_unwind(payload),
+        },
+    }
+}
+
+impl<F: ?Sized> Debug for DefaultInit<F> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::None => write!(f, "None"),
             Self::Infailable(_) => write!(f, "Infailable"),
             Self::Failable(_) => write!(f, "Failable"),
+            Self::Cancellable(_) => write!(f, "Cancellable"),
+        }
+    }
+}
+
+impl<F: ?Sized> DefaultInit<F> {
+    /// Runs the *default* initializer, passing `token` to it. If a
+    /// *cancellable* initializer fails after `token` was cancelled, an error
+    /// of type [`Timeout`](InitError) is returned.
+    pub fn call<P>(&self, token: &CancellationToken) -> Result<P, InitError>
+    where
+        F: Fn(&CancellationToken) -> Result<P, InitError>,
+    {
+        match self {
+            DefaultInit::Infailable(init) | DefaultInit::Failable(init) => init(token),
+            DefaultInit::Cancellable(init) => init(token).map_err(|error| match token.is_cancelled() {
+                true => InitError::Timeout,
+                false => error,
+            }),
+            DefaultInit::None => Err(InitError::NoDefaultInitializer),
         }
     }
 }
 
+/// Calls the *infailable* initializer `init`, which never returns an error.
+pub fn call_infailable<T, F>(init: &F) -> T
+where
+    F: ?Sized + Fn(&CancellationToken) -> Result<T, InitError>,
+{
+    init(&CancellationToken::new()).unwrap_or_else(|_| unreachable!("Infailable initializer has failed!"))
+}
+
 impl Display for InitError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             InitError::NoDefaultInitializer => write!(f, "No default initializer available!"),
             InitError::Failed(error) => Display::fmt(&error, f),
+            InitError::Env { name, error } => write!(f, "Environment variable \"{}\": {}", name, error),
+            InitError::File { path, error } => write!(f, "File \"{}\": {}", path.display(), error),
+            InitError::Timeout => write!(f, "Initialization has timed out!"),
         }
     }
 }
 
-impl Error for InitError {}
+impl Error for InitError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            InitError::NoDefaultInitializer | InitError::Timeout => None,
+            InitError::Failed(error) => Some(error),
+            InitError::Env { error, .. } | InitError::File { error, .. } => Some(error.as_ref()),
+        }
+    }
+}
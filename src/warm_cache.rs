@@ -0,0 +1,150 @@
+/*
+ * lazy_rc - Rc<T> and Arc<T> with *lazy* initialization
+ * This is free and unencumbered software released into the public domain.
+ */
+use std::fs::{self, File};
+use std::io::{Error as IoError, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::str;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+use crate::InitError;
+
+const MAGIC: &str = "lazy_rc-cache";
+const FORMAT: &str = "1";
+
+/// Makes the name of each temporary file unique within this process.
+static TEMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// A persistent on-disk cache for the result of an expensive initializer
+///
+/// A `WarmCache` stores the *serialized* result of an initializer in a file,
+/// so that subsequent process starts can *deserialize* the value from the
+/// file, rather than recomputing it ("warm start"). The file is identified by
+/// a `key` and a `version`; if either does not match, or if the file is
+/// corrupt (checksum mismatch), the cached value is ignored and the value is
+/// recomputed.
+///
+/// Cache files are written *atomically*, i.e. a concurrent reader sees either
+/// the previous or the new file, but never a partially written file.
+///
+/// This type is only available with the `persist` feature enabled.
+#[derive(Clone, Debug)]
+pub struct WarmCache {
+    path: PathBuf,
+    key: String,
+    version: u64,
+}
+
+impl WarmCache {
+    /// Create a new `WarmCache` that stores the value in the file at `path`.
+    ///
+    /// The `key` identifies the cached value, and the `version` should be
+    /// incremented whenever the format of the value or the way it is computed
+    /// changes, so that outdated cache files are ignored.
+    pub fn new<P, K>(path: P, key: K, version: u64) -> Self
+    where
+        P: Into<PathBuf>,
+        K: Into<String>,
+    {
+        Self {
+            path: path.into(),
+            key: key.into(),
+            version,
+        }
+    }
+
+    /// Returns the path of the cache file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Loads the cached value, if a *valid* cache file exists.
+    ///
+    /// Returns `None`, if the cache file does **not** exist, if its key or
+    /// version does **not** match, if the checksum does **not** match, or if
+    /// the value can **not** be deserialized.
+    pub fn load<T>(&self) -> Option<T>
+    where
+        T: DeserializeOwned,
+    {
+        let data = fs::read(&self.path).ok()?;
+        let split = data.iter().position(|byte| *byte == b'\n')?;
+        let (header, payload) = (str::from_utf8(&data[..split]).ok()?, &data[split + 1..]);
+        let mut fields = header.splitn(6, ' ');
+        let valid = fields.next() == Some(MAGIC)
+            && fields.next() == Some(FORMAT)
+            && fields.next()?.parse::<u64>().ok()? == self.version
+            && fields.next()?.parse::<usize>().ok()? == payload.len()
+            && u64::from_str_radix(fields.next()?, 16).ok()? == checksum(payload)
+            && serde_json::from_str::<String>(fields.next()?).ok()? == self.key;
+        match valid {
+            true => serde_json::from_slice(payload).ok(),
+            false => None,
+        }
+    }
+
+    /// Stores `value` in the cache file, replacing the existing file.
+    ///
+    /// The value is written to a temporary file first, which is then renamed
+    /// to the actual cache file. Errors are reported as [`InitError::File`].
+    pub fn store<T>(&self, value: &T) -> Result<(), InitError>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.write(value).map_err(|error| InitError::File { path: self.path.clone(), error: error.into() })
+    }
+
+    /// Loads the cached value, or computes the value right now.
+    ///
+    /// If and only if **no** *valid* cache file exists, the function
+    /// `init_fn()` is called to compute the value. If `init_fn()` succeeds,
+    /// the value is [stored](Self::store) in the cache file. Storing the value
+    /// is *best effort*, i.e. a failure to write the cache file does **not**
+    /// cause this function to fail. If `init_fn()` fails, the error is passed
+    /// through.
+    pub fn load_or_init<T, E, F>(&self, init_fn: F) -> Result<T, E>
+    where
+        T: Serialize + DeserializeOwned,
+        F: FnOnce() -> Result<T, E>,
+    {
+        if let Some(value) = self.load() {
+            return Ok(value);
+        }
+        let value = init_fn()?;
+        let _ = self.store(&value);
+        Ok(value)
+    }
+
+    fn write<T>(&self, value: &T) -> Result<(), IoError>
+    where
+        T: Serialize + ?Sized,
+    {
+        let payload = serde_json::to_vec(value)?;
+        let key = serde_json::to_string(&self.key)?;
+        let file_name = self.path.file_name().ok_or_else(|| IoError::from(ErrorKind::InvalidInput))?;
+        let mut temp_name = file_name.to_os_string();
+        temp_name.push(format!(".{}.{}.tmp", process::id(), TEMP_COUNTER.fetch_add(1, Ordering::Relaxed)));
+        let temp_path = self.path.with_file_name(temp_name);
+        let result = (|| {
+            let mut file = File::create(&temp_path)?;
+            writeln!(file, "{} {} {} {} {:016x} {}", MAGIC, FORMAT, self.version, payload.len(), checksum(&payload), key)?;
+            file.write_all(&payload)?;
+            file.sync_all()?;
+            fs::rename(&temp_path, &self.path)
+        })();
+        if result.is_err() {
+            let _ = fs::remove_file(&temp_path);
+        }
+        result
+    }
+}
+
+/// Computes the 64-Bit FNV-1a hash of `data`.
+fn checksum(data: &[u8]) -> u64 {
+    data.iter().fold(0xcbf29ce484222325, |hash, byte| (hash ^ u64::from(*byte)).wrapping_mul(0x100000001b3))
+}
@@ -0,0 +1,59 @@
+/*
+ * lazy_rc - Rc<T> and Arc<T> with *lazy* initialization
+ * This is free and unencumbered software released into the public domain.
+ */
+use std::cell::Cell;
+use std::rc::Rc;
+use std::sync::MutexGuard;
+
+use static_assertions::{assert_impl_all, assert_not_impl_any};
+
+use lazy_rc::{LazyArc, LazyFn, LazyRc, LazyRcFn, Subscription};
+
+// `LazyArc<T>` is `Send + Sync` if and only if `T` is `Send + Sync`
+assert_impl_all!(LazyArc<u32>: Send, Sync);
+assert_impl_all!(LazyArc<String>: Send, Sync);
+assert_not_impl_any!(LazyArc<Cell<u32>>: Send, Sync);
+assert_not_impl_any!(LazyArc<Rc<u32>>: Send, Sync);
+assert_not_impl_any!(LazyArc<MutexGuard<'static, u32>>: Send, Sync);
+assert_impl_all!(LazyArc<str>: Send, Sync);
+assert_impl_all!(LazyArc<dyn Fn() + Send + Sync>: Send, Sync);
+assert_not_impl_any!(LazyArc<dyn Fn()>: Send, Sync);
+
+// `Subscription<T>` can be moved to the thread that waits for notifications
+assert_impl_all!(Subscription<u32>: Send, Sync);
+assert_not_impl_any!(Subscription<Rc<u32>>: Send, Sync);
+
+// `LazyRc<T>` is never `Send` or `Sync`, just like `Rc<T>`
+assert_not_impl_any!(LazyRc<u32>: Send, Sync);
+assert_not_impl_any!(LazyRc<String>: Send, Sync);
+
+// `LazyFn<A, R>` can be shared by multiple threads, `LazyRcFn<A, R>` can not
+assert_impl_all!(LazyFn<String, u32>: Send, Sync);
+assert_not_impl_any!(LazyFn<String, Rc<u32>>: Send, Sync);
+assert_not_impl_any!(LazyRcFn<String, u32>: Send, Sync);
+
+#[test]
+fn lazy_arc_with_default_init_is_send() {
+    let lazy = LazyArc::with_default_init(|| 42u32);
+    let value = std::thread::spawn(move || lazy.or_init()).join().unwrap();
+    assert_eq!(*value, 42u32);
+}
+
+#[test]
+fn lazy_arc_with_failable_default_init_is_send() {
+    let lazy = LazyArc::with_failable_default_init(|| Ok(42u32));
+    let value = std::thread::spawn(move || lazy.or_try_init().unwrap()).join().unwrap();
+    assert_eq!(*value, 42u32);
+}
+
+#[test]
+fn lazy_rc_default_init_may_capture_rc() {
+    let shared = Rc::new(42u32);
+    let lazy = LazyRc::with_default_init({
+        let shared = Rc::clone(&shared);
+        move || *shared
+    });
+    assert_eq!(*lazy.or_init(), 42u32);
+    assert_eq!(Rc::strong_count(&shared), 2);
+}
@@ -0,0 +1,95 @@
+/*
+ * lazy_rc - Rc<T> and Arc<T> with *lazy* initialization
+ * This is free and unencumbered software released into the public domain.
+ */
+#![cfg(feature = "memory-budget")]
+
+use std::any::type_name;
+use std::sync::{Arc, Barrier, LazyLock};
+use std::thread;
+
+use lazy_rc::{BudgetEntry, LazyArc, MemoryBudget};
+
+type Cache = LazyLock<LazyArc<Vec<u8>>>;
+
+fn cache(size: usize) -> LazyArc<Vec<u8>> {
+    LazyArc::with_default_init(move || vec![0u8; size])
+}
+
+#[test]
+fn budget_evicts_least_recently_used() {
+    static BUDGET: MemoryBudget = MemoryBudget::new(250);
+    static FIRST: Cache = LazyLock::new(|| cache(100));
+    static SECOND: Cache = LazyLock::new(|| cache(100));
+    static THIRD: Cache = LazyLock::new(|| cache(100));
+    for lazy in [&FIRST, &SECOND, &THIRD] {
+        lazy.register_budget(&BUDGET, Vec::len);
+    }
+    FIRST.or_init();
+    SECOND.or_init();
+    FIRST.or_init();
+    THIRD.or_init();
+    assert!(FIRST.is_initialized() && !SECOND.is_initialized() && THIRD.is_initialized());
+    assert_eq!(BUDGET.usage(), 200);
+    assert_eq!(SECOND.budget_usage(), Some(0));
+}
+
+#[test]
+fn budget_skips_instances_without_default_init() {
+    static BUDGET: MemoryBudget = MemoryBudget::new(150);
+    static PINNED: LazyArc<Vec<u8>> = LazyArc::empty();
+    static EVICTABLE: Cache = LazyLock::new(|| cache(100));
+    PINNED.register_budget(&BUDGET, Vec::len);
+    EVICTABLE.register_budget(&BUDGET, Vec::len);
+    EVICTABLE.or_init();
+    PINNED.or_init_with(|| vec![0u8; 100]);
+    assert!(PINNED.is_initialized() && !EVICTABLE.is_initialized());
+
+    BUDGET.set_limit(50);
+    assert!(PINNED.is_initialized());
+    assert_eq!(BUDGET.usage(), 100);
+}
+
+#[test]
+fn budget_entries_and_set_limit() {
+    static BUDGET: MemoryBudget = MemoryBudget::new(1000);
+    static SMALL: Cache = LazyLock::new(|| cache(10));
+    static LARGE: LazyLock<LazyArc<String>> = LazyLock::new(|| LazyArc::with_default_init(|| "x".repeat(500)));
+    SMALL.register_budget(&BUDGET, Vec::len);
+    LARGE.register_budget(&BUDGET, String::len);
+    assert_eq!(BUDGET.limit(), 1000);
+    SMALL.or_init();
+    LARGE.or_init();
+    assert_eq!(BUDGET.entries(), vec![
+        BudgetEntry { type_name: type_name::<Vec<u8>>(), usage: 10, evictable: true },
+        BudgetEntry { type_name: type_name::<String>(), usage: 500, evictable: true },
+    ]);
+    SMALL.or_init();
+    BUDGET.set_limit(100);
+    assert_eq!(BUDGET.limit(), 100);
+    assert!(SMALL.is_initialized() && !LARGE.is_initialized());
+    assert_eq!(BUDGET.entries()[1].usage, 0);
+}
+
+#[test]
+fn budget_is_enforced_under_concurrent_initialization() {
+    static BUDGET: MemoryBudget = MemoryBudget::new(300);
+    static CACHES: LazyLock<Vec<&'static LazyArc<Vec<u8>>>> = LazyLock::new(|| {
+        (0..16).map(|_| &*Box::leak(Box::new(cache(100)))).collect()
+    });
+    for lazy in CACHES.iter() {
+        lazy.register_budget(&BUDGET, Vec::len);
+    }
+    let barrier = Arc::new(Barrier::new(CACHES.len()));
+    let threads: Vec<_> = CACHES.iter().map(|lazy| {
+        let barrier = Arc::clone(&barrier);
+        thread::spawn(move || {
+            barrier.wait();
+            lazy.or_init();
+        })
+    }).collect();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+    assert!(BUDGET.usage() <= BUDGET.limit(), "Usage {} exceeds the limit", BUDGET.usage());
+}
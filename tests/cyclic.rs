@@ -0,0 +1,44 @@
+/*
+ * lazy_rc - Rc<T> and Arc<T> with *lazy* initialization
+ * This is free and unencumbered software released into the public domain.
+ */
+use std::io::{Error, ErrorKind};
+use std::rc::{self, Rc};
+use std::sync::{self, Arc};
+
+use lazy_rc::{LazyArc, LazyRc};
+
+struct SyncService {
+    this: sync::Weak<SyncService>,
+}
+
+struct LocalService {
+    this: rc::Weak<LocalService>,
+}
+
+#[test]
+fn lazy_arc_or_init_cyclic() {
+    let lazy: LazyArc<SyncService> = LazyArc::empty();
+    let value = lazy.or_init_cyclic(|weak| SyncService { this: weak.clone() });
+    assert!(Arc::ptr_eq(&value, &value.this.upgrade().unwrap()));
+}
+
+#[test]
+fn lazy_arc_or_try_init_cyclic() {
+    let lazy: LazyArc<SyncService> = LazyArc::empty();
+    let error = lazy.or_try_init_cyclic(|_| Err(Error::from(ErrorKind::NotFound))).err().unwrap();
+    assert_eq!(error.kind(), ErrorKind::NotFound);
+    assert!(!lazy.is_initialized());
+    let value = lazy.or_try_init_cyclic(|weak| Ok::<_, Error>(SyncService { this: weak.clone() })).unwrap();
+    assert!(Arc::ptr_eq(&value, &value.this.upgrade().unwrap()));
+}
+
+#[test]
+fn lazy_rc_or_try_init_cyclic() {
+    let lazy: LazyRc<LocalService> = LazyRc::empty();
+    let error = lazy.or_try_init_cyclic(|_| Err(Error::from(ErrorKind::NotFound))).err().unwrap();
+    assert_eq!(error.kind(), ErrorKind::NotFound);
+    assert!(!lazy.is_initialized());
+    let value = lazy.or_try_init_cyclic(|weak| Ok::<_, Error>(LocalService { this: weak.clone() })).unwrap();
+    assert!(Rc::ptr_eq(&value, &value.this.upgrade().unwrap()));
+}
@@ -0,0 +1,57 @@
+/*
+ * lazy_rc - Rc<T> and Arc<T> with *lazy* initialization
+ * This is free and unencumbered software released into the public domain.
+ */
+use std::io::{Error, ErrorKind};
+use std::sync::Arc;
+use std::thread;
+
+use static_assertions::{assert_impl_all, assert_not_impl_any};
+
+use lazy_rc::{ArcFamily, Lazy, LazyArc, LazyRc, LockFamily, MutexFamily, PointerFamily, RcFamily, RefCellFamily, RwLockFamily};
+
+// The auto traits follow from the pointer and the lock family
+assert_impl_all!(Lazy<u32, ArcFamily, MutexFamily>: Send, Sync);
+assert_not_impl_any!(Lazy<u32, ArcFamily, RefCellFamily>: Sync);
+assert_not_impl_any!(Lazy<u32, RcFamily, RwLockFamily>: Send, Sync);
+
+/// Exercises the common API, regardless of the pointer and lock families.
+fn exercise<P: PointerFamily, L: LockFamily>(lazy: Lazy<String, P, L>) {
+    assert!(!lazy.is_initialized());
+    assert_eq!(lazy.or_try_init_with(|| Err(Error::from(ErrorKind::NotFound))).err().map(|error| error.kind()), Some(ErrorKind::NotFound));
+    let value = lazy.or_init_with(|| String::from("a"));
+    assert_eq!(value.as_str(), "a");
+    assert!(P::ptr_eq(&value, &lazy.value().unwrap()));
+    assert_eq!(lazy.replace(P::new(String::from("b"))).as_deref().map(String::as_str), Some("a"));
+    assert_eq!(lazy.map(|value| value.len()), Some(1));
+    assert_eq!(lazy.reset().as_deref().map(String::as_str), Some("b"));
+    assert!(lazy.value().is_none());
+    assert_eq!(lazy.generation(), 3);
+}
+
+#[test]
+fn lazy_with_all_families() {
+    exercise(Lazy::<String, ArcFamily, RwLockFamily>::empty());
+    exercise(Lazy::<String, ArcFamily, MutexFamily>::empty());
+    exercise(Lazy::<String, RcFamily, RefCellFamily>::empty());
+    exercise(Lazy::<String, RcFamily, MutexFamily>::empty());
+}
+
+#[test]
+fn lazy_arc_mutex_is_shared_by_threads() {
+    let lazy: Arc<Lazy<u32, ArcFamily, MutexFamily>> = Arc::new(Lazy::with_default_init(|| 7));
+    let threads: Vec<_> = (0..4).map(|_| {
+        let lazy = Arc::clone(&lazy);
+        thread::spawn(move || *lazy.or_init())
+    }).collect();
+    for thread in threads {
+        assert_eq!(thread.join().unwrap(), 7);
+    }
+}
+
+#[test]
+fn debug_output_is_named_after_the_pointer_family() {
+    assert_eq!(format!("{:?}", LazyArc::<u32>::empty()), "LazyArc { default_init: None, state: Uninitialized }");
+    assert_eq!(format!("{:?}", LazyRc::<u32>::empty()), "LazyRc { default_init: None, state: Uninitialized }");
+    assert!(format!("{:?}", Lazy::<u32, ArcFamily, MutexFamily>::from(7)).starts_with("LazyArc { default_init: None, state: Initialized"));
+}
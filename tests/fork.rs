@@ -0,0 +1,115 @@
+/*
+ * lazy_rc - Rc<T> and Arc<T> with *lazy* initialization
+ * This is free and unencumbered software released into the public domain.
+ */
+#![cfg(all(unix, feature = "fork"))]
+
+use std::sync::LazyLock;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::thread;
+use std::time::Duration;
+
+use lazy_rc::{ForkPolicy, LazyArc, State};
+
+static KEPT: LazyArc<u32> = LazyArc::empty();
+static RESET: LazyArc<u32> = LazyArc::empty();
+static SLOW: LazyArc<u32> = LazyArc::empty();
+
+static COUNTER: AtomicU32 = AtomicU32::new(0);
+static REINITIALIZED: LazyLock<LazyArc<u32>> = LazyLock::new(|| {
+    LazyArc::with_default_init(|| COUNTER.fetch_add(1, Ordering::SeqCst) + 1)
+});
+
+/// Runs `child_fn()` in a forked child process and returns its exit status.
+fn fork_and_wait<F: FnOnce() -> bool>(child_fn: F) -> i32 {
+    match unsafe { libc::fork() } {
+        -1 => panic!("Failed to fork the process!"),
+        0 => unsafe { libc::_exit(if child_fn() { 0 } else { 1 }) },
+        pid => {
+            let mut status = 0;
+            assert_eq!(unsafe { libc::waitpid(pid, &mut status, 0) }, pid);
+            assert!(libc::WIFEXITED(status));
+            libc::WEXITSTATUS(status)
+        },
+    }
+}
+
+#[test]
+fn fork_policy_keep() {
+    KEPT.set_fork_policy(ForkPolicy::Keep);
+    KEPT.or_init_with(|| 42);
+    assert_eq!(fork_and_wait(|| KEPT.is_initialized() && *KEPT.or_init_with(|| 0) == 42), 0);
+    assert_eq!(*KEPT.or_init_with(|| 0), 42);
+}
+
+#[test]
+fn fork_policy_reset_in_child() {
+    RESET.set_fork_policy(ForkPolicy::ResetInChild);
+    RESET.or_init_with(|| 42);
+    assert_eq!(fork_and_wait(|| !RESET.is_initialized() && *RESET.or_init_with(|| 7) == 7), 0);
+    assert_eq!(*RESET.or_init_with(|| 0), 42);
+}
+
+#[test]
+fn fork_policy_reinitialize_in_child() {
+    REINITIALIZED.set_fork_policy(ForkPolicy::ReinitializeInChild);
+    assert_eq!(*REINITIALIZED.or_init(), 1);
+    assert_eq!(fork_and_wait(|| REINITIALIZED.value().is_some_and(|value| *value == 2) && *REINITIALIZED.or_init() == 2), 0);
+    assert_eq!(*REINITIALIZED.or_init(), 1);
+}
+
+#[test]
+fn fork_waits_for_pending_initialization() {
+    SLOW.set_fork_policy(ForkPolicy::Keep);
+    let initializer = thread::spawn(|| {
+        SLOW.or_init_with(|| {
+            thread::sleep(Duration::from_millis(250));
+            42
+        });
+    });
+    while !matches!(SLOW.state(), State::Initializing { .. }) {
+        thread::yield_now();
+    }
+    assert_eq!(fork_and_wait(|| SLOW.is_initialized() && *SLOW.or_init_with(|| 0) == 42), 0);
+    initializer.join().unwrap();
+}
+
+static DEPENDENCY: LazyArc<u32> = LazyArc::empty();
+static DEPENDENT: LazyArc<u32> = LazyArc::empty();
+
+#[test]
+fn fork_waits_for_initializer_that_reads_another_instance() {
+    DEPENDENCY.set_fork_policy(ForkPolicy::Keep);
+    DEPENDENT.set_fork_policy(ForkPolicy::Keep);
+    let initializer = thread::spawn(|| {
+        DEPENDENT.or_init_with(|| {
+            thread::sleep(Duration::from_millis(250));
+            *DEPENDENCY.or_init_with(|| 41) + 1
+        });
+    });
+    while !matches!(DEPENDENT.state(), State::Initializing { .. }) {
+        thread::yield_now();
+    }
+    assert_eq!(fork_and_wait(|| *DEPENDENT.or_init_with(|| 0) == 42 && *DEPENDENCY.or_init_with(|| 0) == 41), 0);
+    initializer.join().unwrap();
+}
+
+static REGISTERING: LazyArc<u32> = LazyArc::empty();
+static REGISTERED: LazyArc<u32> = LazyArc::empty();
+
+#[test]
+fn fork_waits_for_initializer_that_sets_fork_policy() {
+    REGISTERING.set_fork_policy(ForkPolicy::Keep);
+    let initializer = thread::spawn(|| {
+        REGISTERING.or_init_with(|| {
+            thread::sleep(Duration::from_millis(250));
+            REGISTERED.set_fork_policy(ForkPolicy::ResetInChild);
+            *REGISTERED.or_init_with(|| 7)
+        });
+    });
+    while !matches!(REGISTERING.state(), State::Initializing { .. }) {
+        thread::yield_now();
+    }
+    assert_eq!(fork_and_wait(|| *REGISTERING.or_init_with(|| 0) == 7 && !REGISTERED.is_initialized()), 0);
+    initializer.join().unwrap();
+}
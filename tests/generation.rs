@@ -0,0 +1,54 @@
+/*
+ * lazy_rc - Rc<T> and Arc<T> with *lazy* initialization
+ * This is free and unencumbered software released into the public domain.
+ */
+use lazy_rc::{LazyArc, LazyRc};
+
+#[test]
+fn lazy_arc_generation_counts_changes() {
+    let lazy: LazyArc<u32> = LazyArc::empty();
+    assert_eq!(lazy.generation(), 0);
+    lazy.or_init_with(|| 1);
+    assert_eq!(lazy.generation(), 1);
+    lazy.or_init_with(|| 2);
+    assert_eq!(lazy.generation(), 1);
+    lazy.replace(3);
+    assert_eq!(lazy.generation(), 2);
+    lazy.reset();
+    assert_eq!(lazy.generation(), 3);
+    assert_eq!(lazy.value_with_generation(), None);
+    assert_eq!(LazyArc::<u32>::from(4).generation(), 1);
+}
+
+#[test]
+fn lazy_arc_handle_is_current() {
+    let lazy: LazyArc<u32> = LazyArc::empty();
+    assert!(lazy.handle().is_none());
+    lazy.or_init_with(|| 1);
+    let mut handle = lazy.handle().unwrap();
+    assert!(handle.is_current());
+    assert!(!handle.refresh());
+
+    lazy.replace(2);
+    assert!(!handle.is_current());
+    assert_eq!(*handle, 1);
+    assert!(handle.refresh());
+    assert!(handle.is_current());
+    assert_eq!((*handle, handle.generation()), (2, 2));
+
+    lazy.reset();
+    assert!(!handle.is_current());
+    assert!(!handle.refresh());
+    assert_eq!(*handle.into_inner(), 2);
+}
+
+#[test]
+fn lazy_rc_handle_is_current() {
+    let lazy: LazyRc<String> = LazyRc::from(String::from("a"));
+    let handle = lazy.handle().unwrap();
+    let (value, generation) = lazy.value_with_generation().unwrap();
+    assert_eq!((handle.value().as_str(), handle.generation()), (value.as_str(), generation));
+    lazy.replace(String::from("b"));
+    assert!(!handle.is_current());
+    assert!(lazy.handle().unwrap().is_current());
+}
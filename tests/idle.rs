@@ -0,0 +1,106 @@
+/*
+ * lazy_rc - Rc<T> and Arc<T> with *lazy* initialization
+ * This is free and unencumbered software released into the public domain.
+ */
+#![cfg(all(feature = "idle-timeout", feature = "test-util"))]
+
+use std::sync::{Arc, LazyLock};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use lazy_rc::LazyArc;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+
+fn counting_lazy(counter: &'static AtomicU32) -> LazyArc<u32> {
+    LazyArc::with_default_init(|| counter.fetch_add(1, Ordering::SeqCst) + 1)
+}
+
+#[test]
+fn idle_value_is_evicted_on_access() {
+    static COUNTER: AtomicU32 = AtomicU32::new(0);
+    let lazy = counting_lazy(&COUNTER);
+    lazy.set_idle_timeout(Some(TIMEOUT));
+    assert_eq!(lazy.idle_timeout(), Some(TIMEOUT));
+    let value = lazy.or_init();
+    assert_eq!(*value, 1);
+    assert_eq!(*lazy.or_init(), 1);
+    lazy.advance_idle_clock(TIMEOUT * 2);
+    assert_eq!(*lazy.or_init(), 2);
+    assert_eq!(*value, 1);
+}
+
+#[test]
+fn accessed_value_is_not_evicted() {
+    static COUNTER: AtomicU32 = AtomicU32::new(0);
+    let lazy = counting_lazy(&COUNTER);
+    lazy.set_idle_timeout(Some(TIMEOUT));
+    lazy.or_init();
+    for _ in 0..10 {
+        lazy.advance_idle_clock(TIMEOUT / 2);
+        assert_eq!(*lazy.or_init(), 1);
+        assert!(!lazy.evict_if_idle());
+    }
+}
+
+#[test]
+fn evict_if_idle_does_not_count_as_access() {
+    static COUNTER: AtomicU32 = AtomicU32::new(0);
+    let lazy = counting_lazy(&COUNTER);
+    assert!(!lazy.evict_if_idle());
+    lazy.set_idle_timeout(Some(TIMEOUT));
+    lazy.or_init();
+    assert!(!lazy.evict_if_idle());
+    lazy.advance_idle_clock(TIMEOUT / 2);
+    assert!(!lazy.evict_if_idle());
+    lazy.advance_idle_clock(TIMEOUT / 2 + Duration::from_millis(1));
+    assert!(lazy.evict_if_idle());
+    assert!(!lazy.is_initialized());
+    assert!(!lazy.evict_if_idle());
+}
+
+#[test]
+fn value_without_default_init_is_not_evicted() {
+    let lazy: LazyArc<u32> = LazyArc::empty();
+    lazy.set_idle_timeout(Some(TIMEOUT));
+    lazy.or_init_with(|| 1);
+    lazy.advance_idle_clock(TIMEOUT * 2);
+    assert!(!lazy.evict_if_idle());
+    assert_eq!(*lazy.or_init_with(|| 2), 1);
+}
+
+#[test]
+fn concurrent_accesses_evict_once() {
+    static COUNTER: AtomicU32 = AtomicU32::new(0);
+    let lazy = Arc::new(counting_lazy(&COUNTER));
+    lazy.set_idle_timeout(Some(TIMEOUT));
+    lazy.or_init();
+    lazy.advance_idle_clock(TIMEOUT * 2);
+    let threads: Vec<_> = (0..8).map(|_| {
+        let lazy = Arc::clone(&lazy);
+        thread::spawn(move || *lazy.or_init())
+    }).collect();
+    for thread in threads {
+        assert_eq!(thread.join().unwrap(), 2);
+    }
+    assert_eq!(COUNTER.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn sweeper_evicts_idle_value() {
+    static COUNTER: AtomicU32 = AtomicU32::new(0);
+    static SWEPT: LazyLock<LazyArc<u32>> = LazyLock::new(|| counting_lazy(&COUNTER));
+    SWEPT.set_idle_timeout(Some(TIMEOUT));
+    SWEPT.or_init();
+    let sweeper = SWEPT.spawn_idle_sweeper(Duration::from_millis(1));
+    SWEPT.advance_idle_clock(TIMEOUT * 2);
+    let start = Instant::now();
+    while SWEPT.is_initialized() {
+        assert!(start.elapsed() < Duration::from_secs(5), "The idle value has not been evicted!");
+        thread::yield_now();
+    }
+    SWEPT.set_idle_timeout(None);
+    sweeper.join().unwrap();
+    assert_eq!(*SWEPT.or_init(), 2);
+}
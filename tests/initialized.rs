@@ -0,0 +1,99 @@
+/*
+ * lazy_rc - Rc<T> and Arc<T> with *lazy* initialization
+ * This is free and unencumbered software released into the public domain.
+ */
+use std::future::Future;
+use std::io::{Error, ErrorKind};
+use std::pin::{pin, Pin};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread;
+use std::time::Duration;
+
+use lazy_rc::{LazyArc, LazyRc};
+
+/// A waker that records whether it has been woken.
+#[derive(Default)]
+struct Flag(AtomicBool);
+
+impl Wake for Flag {
+    fn wake(self: Arc<Self>) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+}
+
+impl Flag {
+    fn take(&self) -> bool {
+        self.0.swap(false, Ordering::SeqCst)
+    }
+}
+
+fn poll<F: Future>(future: Pin<&mut F>, flag: &Arc<Flag>) -> Poll<F::Output> {
+    future.poll(&mut Context::from_waker(&Waker::from(Arc::clone(flag))))
+}
+
+#[test]
+fn lazy_arc_initialized_resolves_immediately() {
+    let lazy: LazyArc<u32> = LazyArc::from(1);
+    let flag = Arc::new(Flag::default());
+    assert!(matches!(poll(pin!(lazy.initialized()), &flag), Poll::Ready(value) if *value == 1));
+}
+
+#[test]
+fn lazy_arc_initialized_wakes_on_or_try_init() {
+    let lazy: LazyArc<u32> = LazyArc::with_failable_default_init(|| Ok(2));
+    let flag = Arc::new(Flag::default());
+    let mut future = pin!(lazy.initialized());
+    assert!(poll(future.as_mut(), &flag).is_pending());
+    assert!(lazy.or_try_init_with(|| Err(Error::from(ErrorKind::NotFound))).is_err());
+    assert!(poll(future.as_mut(), &flag).is_pending());
+    lazy.or_try_init().unwrap();
+    assert!(flag.take());
+    assert!(matches!(poll(future.as_mut(), &flag), Poll::Ready(value) if *value == 2));
+}
+
+#[test]
+fn lazy_arc_initialized_skips_reset() {
+    let lazy: LazyArc<u32> = LazyArc::from(1);
+    lazy.reset();
+    let flag = Arc::new(Flag::default());
+    let mut future = pin!(lazy.initialized());
+    assert!(poll(future.as_mut(), &flag).is_pending());
+    lazy.replace(3);
+    lazy.reset();
+    assert!(flag.take());
+    assert!(poll(future.as_mut(), &flag).is_pending());
+    lazy.replace(4);
+    assert!(flag.take());
+    assert!(matches!(poll(future.as_mut(), &flag), Poll::Ready(value) if *value == 4));
+}
+
+#[test]
+fn lazy_arc_initialized_wakes_across_threads() {
+    let lazy: Arc<LazyArc<u32>> = Arc::new(LazyArc::empty());
+    let flag = Arc::new(Flag::default());
+    let mut future = pin!(lazy.initialized());
+    assert!(poll(future.as_mut(), &flag).is_pending());
+    let writer = {
+        let lazy = Arc::clone(&lazy);
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(10));
+            lazy.or_init_with(|| 4);
+        })
+    };
+    writer.join().unwrap();
+    assert!(flag.take());
+    assert!(matches!(poll(future.as_mut(), &flag), Poll::Ready(value) if *value == 4));
+}
+
+#[test]
+fn lazy_rc_initialized() {
+    let lazy: LazyRc<u32> = LazyRc::empty();
+    let flag = Arc::new(Flag::default());
+    let mut future = pin!(lazy.initialized());
+    assert!(poll(future.as_mut(), &flag).is_pending());
+    lazy.or_init_with(|| 5);
+    assert!(flag.take());
+    assert!(matches!(poll(future.as_mut(), &flag), Poll::Ready(value) if *value == 5));
+}
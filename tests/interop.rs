@@ -0,0 +1,91 @@
+/*
+ * lazy_rc - Rc<T> and Arc<T> with *lazy* initialization
+ * This is free and unencumbered software released into the public domain.
+ */
+use std::cell::{LazyCell, OnceCell};
+use std::rc::Rc;
+use std::sync::{Arc, LazyLock, OnceLock};
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use lazy_rc::{LazyArc, LazyInit, LazyRc};
+
+fn get_or_default<C: LazyInit<u32>>(cell: &C) -> u32 {
+    *cell.get_or_init_with(|| 42)
+}
+
+#[test]
+fn lazy_arc_once_lock_round_trip() {
+    let lazy: LazyArc<u32> = LazyArc::from(OnceLock::from(Arc::new(1)));
+    assert_eq!(lazy.value().as_deref(), Some(&1));
+    let once_lock = lazy.into_once_lock();
+    assert_eq!(once_lock.get().map(|value| **value), Some(1));
+
+    let lazy: LazyArc<u32> = LazyArc::from(OnceLock::new());
+    assert!(!lazy.is_initialized());
+    assert!(LazyArc::<u32>::with_default_init(|| 2).into_once_lock().get().is_none());
+}
+
+#[test]
+fn lazy_rc_once_cell_round_trip() {
+    let lazy: LazyRc<str> = LazyRc::from(OnceCell::from(Rc::<str>::from("Hello")));
+    assert_eq!(lazy.value().as_deref(), Some("Hello"));
+    let once_cell = lazy.into_once_cell();
+    assert_eq!(once_cell.get().map(|value| &**value), Some("Hello"));
+    assert!(LazyRc::<u32>::empty().into_once_cell().get().is_none());
+}
+
+#[test]
+fn lazy_init_is_generic() {
+    assert_eq!(get_or_default(&LazyArc::<u32>::empty()), 42);
+    assert_eq!(get_or_default(&LazyRc::<u32>::from(1)), 1);
+    assert_eq!(get_or_default(&OnceLock::<u32>::new()), 42);
+    assert_eq!(get_or_default(&OnceCell::from(2u32)), 2);
+
+    let cell = OnceLock::new();
+    assert!(!LazyInit::<u32>::is_initialized(&cell));
+    get_or_default(&cell);
+    assert!(LazyInit::<u32>::is_initialized(&cell));
+}
+
+#[test]
+fn lazy_arc_from_lazy_lock() {
+    static CALLS: AtomicU32 = AtomicU32::new(0);
+    let lazy: LazyArc<u32> = LazyArc::from(LazyLock::new(|| {
+        CALLS.fetch_add(1, Ordering::SeqCst);
+        Arc::new(7)
+    }));
+    assert!(!lazy.is_initialized());
+    assert_eq!(CALLS.load(Ordering::SeqCst), 0);
+    let value = lazy.or_init();
+    assert_eq!(*value, 7);
+    assert!(Arc::ptr_eq(&value, &lazy.reset().unwrap()));
+    assert!(Arc::ptr_eq(&value, &lazy.or_init()));
+    assert_eq!(CALLS.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn lazy_rc_from_lazy_cell() {
+    let lazy: LazyRc<str> = LazyRc::from(LazyCell::new(|| Rc::<str>::from("Hello")));
+    assert!(!lazy.is_initialized());
+    assert_eq!(&*lazy.or_init(), "Hello");
+}
+
+#[cfg(feature = "once_cell")]
+#[test]
+fn lazy_arc_once_cell_crate_round_trip() {
+    let lazy: LazyArc<u32> = LazyArc::from(once_cell::sync::OnceCell::with_value(Arc::new(1)));
+    let once_cell: once_cell::sync::OnceCell<Arc<u32>> = lazy.into();
+    assert_eq!(once_cell.get().map(|value| **value), Some(1));
+    let lazy: LazyRc<u32> = LazyRc::from(once_cell::unsync::OnceCell::new());
+    assert_eq!(get_or_default(&lazy), 42);
+    assert_eq!(get_or_default(&once_cell::unsync::OnceCell::<u32>::new()), 42);
+}
+
+#[cfg(feature = "once_cell")]
+#[test]
+fn lazy_arc_from_once_cell_crate_lazy() {
+    let lazy: LazyArc<u32> = LazyArc::from(once_cell::sync::Lazy::new(|| Arc::new(3)));
+    assert_eq!(*lazy.or_init(), 3);
+    let lazy: LazyRc<u32> = LazyRc::from(once_cell::unsync::Lazy::new(|| Rc::new(4)));
+    assert_eq!(*lazy.or_init(), 4);
+}
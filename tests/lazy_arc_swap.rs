@@ -0,0 +1,91 @@
+/*
+ * lazy_rc - Rc<T> and Arc<T> with *lazy* initialization
+ * This is free and unencumbered software released into the public domain.
+ */
+#![cfg(feature = "arc-swap")]
+
+use std::io::{Error, ErrorKind};
+use std::panic::{AssertUnwindSafe, catch_unwind};
+use std::sync::{Arc, Barrier};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::thread;
+use std::time::Duration;
+
+use lazy_rc::{InitError, LazyArcSwap};
+
+#[test]
+fn lazy_arc_swap_store_and_swap() {
+    let lazy: LazyArcSwap<u32> = LazyArcSwap::empty();
+    lazy.store(1);
+    assert_eq!(*lazy.or_init_with(|| 0), 1);
+    assert_eq!(lazy.swap(2).as_deref(), Some(&1));
+    assert_eq!(lazy.value().as_deref(), Some(&2));
+    assert_eq!(lazy.reset().as_deref(), Some(&2));
+    assert!(!lazy.is_initialized());
+}
+
+#[test]
+fn lazy_arc_swap_compare_and_swap() {
+    let lazy: LazyArcSwap<u32> = LazyArcSwap::from(1);
+    let current = lazy.value();
+    let stale = Some(Arc::new(1));
+    assert!(!Arc::ptr_eq(&lazy.compare_and_swap(&stale, 2).unwrap(), stale.as_ref().unwrap()));
+    assert_eq!(lazy.value().as_deref(), Some(&1));
+    assert!(Arc::ptr_eq(&lazy.compare_and_swap(&current, 3).unwrap(), current.as_ref().unwrap()));
+    assert_eq!(lazy.value().as_deref(), Some(&3));
+}
+
+#[test]
+fn lazy_arc_swap_rcu() {
+    let lazy: LazyArcSwap<u32> = LazyArcSwap::empty();
+    assert_eq!(lazy.rcu(|current| current.map_or(1, |value| **value + 1)), None);
+    assert_eq!(lazy.rcu(|current| current.map_or(1, |value| **value + 1)).as_deref(), Some(&1));
+    assert_eq!(lazy.value().as_deref(), Some(&2));
+}
+
+#[test]
+fn lazy_arc_swap_runs_single_initializer() {
+    static CALLS: AtomicU32 = AtomicU32::new(0);
+    let lazy = Arc::new(LazyArcSwap::with_default_init(|| {
+        thread::sleep(Duration::from_millis(50));
+        CALLS.fetch_add(1, Ordering::SeqCst) + 42
+    }));
+    let barrier = Arc::new(Barrier::new(8));
+    let threads: Vec<_> = (0..8).map(|_| {
+        let (lazy, barrier) = (Arc::clone(&lazy), Arc::clone(&barrier));
+        thread::spawn(move || {
+            barrier.wait();
+            *lazy.or_init()
+        })
+    }).collect();
+    for thread in threads {
+        assert_eq!(thread.join().unwrap(), 42);
+    }
+    assert_eq!(CALLS.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn lazy_arc_swap_survives_panicking_initializer() {
+    let lazy: LazyArcSwap<u32> = LazyArcSwap::empty();
+    assert!(catch_unwind(AssertUnwindSafe(|| lazy.or_init_with(|| panic!("Initializer failed!")))).is_err());
+    assert!(!lazy.is_initialized());
+    assert_eq!(*lazy.or_init_with(|| 42), 42);
+}
+
+#[test]
+fn lazy_arc_swap_failable_default_init() {
+    let calls = Arc::new(AtomicU32::new(0));
+    let lazy: LazyArcSwap<u32> = {
+        let calls = Arc::clone(&calls);
+        LazyArcSwap::with_failable_default_init(move || match calls.fetch_add(1, Ordering::SeqCst) {
+            0 => Err(Error::from(ErrorKind::NotFound)),
+            _ => Ok(7),
+        })
+    };
+    assert!(matches!(lazy.or_try_init(), Err(InitError::Failed(error)) if error.kind() == ErrorKind::NotFound));
+    assert!(!lazy.is_initialized());
+    assert_eq!(*lazy.or_try_init().unwrap(), 7);
+    assert_eq!(*lazy.or_try_init().unwrap(), 7);
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+    assert!(matches!(LazyArcSwap::<u32>::empty().or_try_init(), Err(InitError::NoDefaultInitializer)));
+}
@@ -0,0 +1,70 @@
+/*
+ * lazy_rc - Rc<T> and Arc<T> with *lazy* initialization
+ * This is free and unencumbered software released into the public domain.
+ */
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use lazy_rc::{InitError, LazyFileArc};
+
+fn temp_file(name: &str, content: &str) -> PathBuf {
+    let path = env::temp_dir().join(format!("lazy_rc-file-arc-{}-{}", std::process::id(), name));
+    fs::write(&path, content).unwrap();
+    path
+}
+
+fn parse(text: &str) -> Result<u32, std::num::ParseIntError> {
+    text.trim().parse()
+}
+
+#[test]
+fn lazy_file_arc_reloads_changed_file() {
+    let path = temp_file("changed.txt", "1");
+    let lazy = LazyFileArc::new(path.clone(), parse).with_check_interval(Duration::ZERO);
+    assert_eq!(*lazy.get().unwrap(), 1);
+    fs::write(&path, "1000").unwrap();
+    assert_eq!(*lazy.get().unwrap(), 1000);
+    assert!(lazy.last_error().is_none());
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn lazy_file_arc_respects_check_interval() {
+    let path = temp_file("interval.txt", "1");
+    let lazy = LazyFileArc::new(path.clone(), parse).with_check_interval(Duration::from_secs(3600));
+    assert_eq!(*lazy.get().unwrap(), 1);
+    fs::write(&path, "1000").unwrap();
+    assert_eq!(*lazy.get().unwrap(), 1);
+    assert_eq!(*lazy.reload().unwrap(), 1000);
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn lazy_file_arc_keeps_value_on_failed_reload() {
+    let path = temp_file("failed.txt", "1");
+    let lazy = LazyFileArc::new(path.clone(), parse).with_check_interval(Duration::ZERO);
+    let value = lazy.get().unwrap();
+    fs::write(&path, "not a number").unwrap();
+    assert!(Arc::ptr_eq(&lazy.get().unwrap(), &value));
+    assert!(matches!(lazy.last_error().as_deref(), Some(InitError::File { .. })));
+    fs::write(&path, "2").unwrap();
+    assert_eq!(*lazy.get().unwrap(), 2);
+    assert!(lazy.last_error().is_none());
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn lazy_file_arc_failed_reload_records_last_error() {
+    let path = temp_file("reload.txt", "1");
+    let lazy = LazyFileArc::new(path.clone(), parse);
+    let value = lazy.get().unwrap();
+    fs::write(&path, "not a number").unwrap();
+    let error = lazy.reload().unwrap_err();
+    assert!(matches!(*error, InitError::File { .. }));
+    assert!(Arc::ptr_eq(&lazy.last_error().unwrap(), &error));
+    assert!(Arc::ptr_eq(&lazy.value().unwrap(), &value));
+    fs::remove_file(&path).unwrap();
+}
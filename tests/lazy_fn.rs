@@ -0,0 +1,97 @@
+/*
+ * lazy_rc - Rc<T> and Arc<T> with *lazy* initialization
+ * This is free and unencumbered software released into the public domain.
+ */
+use std::io::{Error, ErrorKind};
+use std::sync::{Arc, Barrier};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::thread;
+
+use lazy_rc::{CacheStats, LazyFn, LazyRcFn};
+
+fn square(arg: &u32) -> Result<u32, Error> {
+    match *arg {
+        0 => Err(Error::from(ErrorKind::InvalidInput)),
+        arg => Ok(arg * arg),
+    }
+}
+
+#[test]
+fn lazy_fn_memoizes_results() {
+    let function: LazyFn<u32, u32> = LazyFn::new(square);
+    let first = function.get(&3).unwrap();
+    assert!(Arc::ptr_eq(&first, &function.get(&3).unwrap()));
+    assert_eq!(*function.get(&4).unwrap(), 16);
+    assert_eq!(function.get(&0).unwrap_err().kind(), ErrorKind::InvalidInput);
+    assert_eq!(function.cached(&0), None);
+    assert_eq!(function.len(), 2);
+    assert_eq!(function.stats(), CacheStats { hits: 1, misses: 3, evictions: 0 });
+
+    assert_eq!(function.remove(&3).as_deref(), Some(&9));
+    function.clear();
+    assert!(function.is_empty());
+}
+
+#[test]
+fn lazy_fn_evicts_least_recently_used() {
+    let function: LazyFn<u32, u32> = LazyFn::with_capacity(2, square);
+    assert_eq!(function.capacity(), Some(2));
+    function.get(&1).unwrap();
+    function.get(&2).unwrap();
+    function.get(&1).unwrap();
+    function.get(&3).unwrap();
+    assert_eq!(function.cached(&1).as_deref(), Some(&1));
+    assert_eq!(function.cached(&2), None);
+    assert_eq!(function.stats().evictions, 1);
+}
+
+#[test]
+fn lazy_fn_computes_once_per_argument() {
+    let calls = Arc::new(AtomicU32::new(0));
+    let function = {
+        let calls = Arc::clone(&calls);
+        Arc::new(LazyFn::<u32, u32>::new(move |arg| {
+            calls.fetch_add(1, Ordering::SeqCst);
+            square(arg)
+        }))
+    };
+    let barrier = Arc::new(Barrier::new(8));
+    let threads: Vec<_> = (0..8).map(|_| {
+        let (function, barrier) = (Arc::clone(&function), Arc::clone(&barrier));
+        thread::spawn(move || {
+            barrier.wait();
+            *function.get(&5).unwrap()
+        })
+    }).collect();
+    for thread in threads {
+        assert_eq!(thread.join().unwrap(), 25);
+    }
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn lazy_rc_fn_memoizes_results() {
+    let function: LazyRcFn<u32, u32> = LazyRcFn::with_capacity(1, square);
+    assert_eq!(*function.get(&2).unwrap(), 4);
+    assert_eq!(*function.get(&2).unwrap(), 4);
+    assert!(function.get(&0).is_err());
+    assert_eq!(*function.get(&3).unwrap(), 9);
+    assert_eq!(function.cached(&2), None);
+    assert_eq!(function.stats(), CacheStats { hits: 1, misses: 3, evictions: 1 });
+}
+
+#[test]
+fn lazy_fn_failure_does_not_count_toward_capacity() {
+    let function: LazyFn<u32, u32> = LazyFn::with_capacity(2, square);
+    function.get(&1).unwrap();
+    function.get(&2).unwrap();
+    assert!(function.get(&0).is_err());
+    assert!(function.get(&0).is_err());
+    assert_eq!(function.len(), 2);
+    assert_eq!(function.stats().evictions, 0);
+    function.get(&3).unwrap();
+    assert_eq!(function.cached(&1), None);
+    assert_eq!(function.cached(&2).as_deref(), Some(&4));
+    assert_eq!(function.cached(&3).as_deref(), Some(&9));
+    assert_eq!(function.stats().evictions, 1);
+}
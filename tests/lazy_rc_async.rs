@@ -0,0 +1,123 @@
+/*
+ * lazy_rc - Rc<T> and Arc<T> with *lazy* initialization
+ * This is free and unencumbered software released into the public domain.
+ */
+use std::cell::Cell;
+use std::future::{Future, poll_fn};
+use std::io::{Error, ErrorKind};
+use std::pin::pin;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::task::{Context, Poll, Wake, Waker};
+
+use lazy_rc::{LazyRc, State};
+
+/// A waker that records whether it has been woken.
+#[derive(Default)]
+struct Flag(AtomicBool);
+
+impl Wake for Flag {
+    fn wake(self: Arc<Self>) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+}
+
+impl Flag {
+    fn take(&self) -> bool {
+        self.0.swap(false, Ordering::SeqCst)
+    }
+}
+
+/// Returns a future that is pending until `open` is set.
+fn gate(open: &Cell<bool>) -> impl Future<Output = ()> + '_ {
+    poll_fn(|_| if open.get() { Poll::Ready(()) } else { Poll::Pending })
+}
+
+fn poll<F: Future>(future: std::pin::Pin<&mut F>, flag: &Arc<Flag>) -> Poll<F::Output> {
+    future.poll(&mut Context::from_waker(&Waker::from(Arc::clone(flag))))
+}
+
+#[test]
+fn lazy_rc_async_tasks_share_one_initialization() {
+    let lazy: LazyRc<u32> = LazyRc::empty();
+    let (open, calls) = (Cell::new(false), Cell::new(0u32));
+    let (first_flag, second_flag) = (Arc::new(Flag::default()), Arc::new(Flag::default()));
+    let mut first = pin!(lazy.or_try_init_with_async(|| async {
+        calls.set(calls.get() + 1);
+        gate(&open).await;
+        Ok::<_, Error>(1)
+    }));
+    let mut second = pin!(lazy.or_try_init_with_async(|| async {
+        calls.set(calls.get() + 1);
+        Ok::<_, Error>(2)
+    }));
+    assert!(poll(first.as_mut(), &first_flag).is_pending());
+    assert!(poll(second.as_mut(), &second_flag).is_pending());
+    assert!(matches!(lazy.state(), State::Initializing { .. }));
+
+    open.set(true);
+    assert!(matches!(poll(first.as_mut(), &first_flag), Poll::Ready(Ok(value)) if *value == 1));
+    assert!(second_flag.take());
+    assert!(matches!(poll(second.as_mut(), &second_flag), Poll::Ready(Ok(value)) if *value == 1));
+    assert_eq!(calls.get(), 1);
+}
+
+#[test]
+fn lazy_rc_async_failure_hands_over_to_waiting_task() {
+    let lazy: LazyRc<u32> = LazyRc::empty();
+    let open = Cell::new(false);
+    let (first_flag, second_flag) = (Arc::new(Flag::default()), Arc::new(Flag::default()));
+    let mut first = pin!(lazy.or_try_init_with_async(|| async {
+        gate(&open).await;
+        Err::<u32, _>(Error::from(ErrorKind::NotFound))
+    }));
+    let mut second = pin!(lazy.or_try_init_with_async(|| async { Ok::<_, Error>(2) }));
+    assert!(poll(first.as_mut(), &first_flag).is_pending());
+    assert!(poll(second.as_mut(), &second_flag).is_pending());
+
+    open.set(true);
+    assert!(matches!(poll(first.as_mut(), &first_flag), Poll::Ready(Err(error)) if error.kind() == ErrorKind::NotFound));
+    assert!(second_flag.take());
+    assert!(matches!(poll(second.as_mut(), &second_flag), Poll::Ready(Ok(value)) if *value == 2));
+}
+
+#[test]
+fn lazy_rc_sync_init_keeps_async_claim() {
+    let lazy: LazyRc<u32> = LazyRc::empty();
+    let open = Cell::new(false);
+    let (first_flag, second_flag) = (Arc::new(Flag::default()), Arc::new(Flag::default()));
+    let mut first = pin!(lazy.or_try_init_with_async(|| async {
+        gate(&open).await;
+        Ok::<_, Error>(1)
+    }));
+    let mut second = pin!(lazy.or_try_init_with_async(|| async { Ok::<_, Error>(2) }));
+    assert!(poll(first.as_mut(), &first_flag).is_pending());
+    assert!(poll(second.as_mut(), &second_flag).is_pending());
+
+    // Synchronous accessors do not wait, but must not release the claim
+    assert_eq!(*lazy.or_init_with(|| 3), 3);
+    assert!(matches!(lazy.state(), State::Initializing { .. }));
+    assert!(poll(second.as_mut(), &second_flag).is_pending());
+
+    open.set(true);
+    assert!(matches!(poll(first.as_mut(), &first_flag), Poll::Ready(Ok(value)) if *value == 3));
+    assert!(matches!(poll(second.as_mut(), &second_flag), Poll::Ready(Ok(value)) if *value == 3));
+    assert!(matches!(lazy.state(), State::Initialized { .. }));
+}
+
+#[test]
+fn lazy_rc_async_dropped_future_releases_claim() {
+    let lazy: LazyRc<u32> = LazyRc::empty();
+    let open = Cell::new(false);
+    let flag = Arc::new(Flag::default());
+    {
+        let mut first = pin!(lazy.or_try_init_with_async(|| async {
+            gate(&open).await;
+            Ok::<_, Error>(1)
+        }));
+        assert!(poll(first.as_mut(), &flag).is_pending());
+    }
+    assert!(!matches!(lazy.state(), State::Initializing { .. }));
+    let mut second = pin!(lazy.or_try_init_with_async(|| async { Ok::<_, Error>(2) }));
+    assert!(matches!(poll(second.as_mut(), &flag), Poll::Ready(Ok(value)) if *value == 2));
+}
@@ -0,0 +1,94 @@
+/*
+ * lazy_rc - Rc<T> and Arc<T> with *lazy* initialization
+ * This is free and unencumbered software released into the public domain.
+ */
+use std::cell::Cell;
+use std::io::{Error, ErrorKind};
+use std::rc::Rc;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use lazy_rc::{LazyArcWith, LazyRcWith, LazyWith, MutexFamily, RcFamily, RefCellFamily, State};
+
+fn parse(arg: &str) -> Result<u32, Error> {
+    arg.parse().map_err(|_| Error::from(ErrorKind::InvalidInput))
+}
+
+#[test]
+fn lazy_arc_with_retries_after_failure() {
+    let lazy: LazyArcWith<u32, str> = LazyArcWith::new(parse);
+    assert_eq!(lazy.or_init_with_arg("invalid").unwrap_err().kind(), ErrorKind::InvalidInput);
+    assert!(!lazy.is_initialized());
+    assert!(matches!(lazy.state(), State::Failed { .. }));
+    assert_eq!(*lazy.or_init_with_arg("42").unwrap(), 42);
+    assert!(lazy.is_initialized());
+}
+
+#[test]
+fn lazy_arc_with_ignores_arg_after_init() {
+    let lazy: LazyArcWith<u32, str> = LazyArcWith::new(parse);
+    assert_eq!(*lazy.or_init_with_arg("1").unwrap(), 1);
+    assert_eq!(*lazy.or_init_with_arg("2").unwrap(), 1);
+    assert_eq!(*lazy.or_init_with_arg("invalid").unwrap(), 1);
+    assert_eq!(lazy.value().as_deref(), Some(&1));
+}
+
+#[test]
+fn lazy_rc_with_retries_after_failure() {
+    let lazy: LazyRcWith<u32, str> = LazyRcWith::new(parse);
+    assert_eq!(lazy.or_init_with_arg("invalid").unwrap_err().kind(), ErrorKind::InvalidInput);
+    assert!(!lazy.is_initialized());
+    assert!(matches!(lazy.state(), State::Failed { .. }));
+    assert_eq!(*lazy.or_init_with_arg("42").unwrap(), 42);
+    assert!(lazy.is_initialized());
+}
+
+#[test]
+fn lazy_rc_with_ignores_arg_after_init() {
+    let lazy: LazyRcWith<u32, str> = LazyRcWith::new(parse);
+    assert_eq!(*lazy.or_init_with_arg("1").unwrap(), 1);
+    assert_eq!(*lazy.or_init_with_arg("2").unwrap(), 1);
+    assert_eq!(*lazy.or_init_with_arg("invalid").unwrap(), 1);
+    assert_eq!(lazy.value().as_deref(), Some(&1));
+}
+
+#[test]
+fn lazy_arc_with_captures_context() {
+    let calls = Arc::new(AtomicU32::new(0));
+    let lazy: LazyArcWith<u32, str> = {
+        let calls = Arc::clone(&calls);
+        LazyArcWith::new(move |arg: &str| {
+            calls.fetch_add(1, Ordering::SeqCst);
+            parse(arg)
+        })
+    };
+    assert_eq!(format!("{:?}", lazy), "LazyArcWith { state: Uninitialized }");
+    assert_eq!(*lazy.or_init_with_arg("7").unwrap(), 7);
+    assert_eq!(*lazy.or_init_with_arg("8").unwrap(), 7);
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn lazy_rc_with_captures_non_send_context() {
+    let calls = Rc::new(Cell::new(0));
+    let lazy: LazyRcWith<u32, str> = {
+        let calls = Rc::clone(&calls);
+        LazyRcWith::new(move |arg: &str| {
+            calls.set(calls.get() + 1);
+            parse(arg)
+        })
+    };
+    assert!(lazy.or_init_with_arg("invalid").is_err());
+    assert_eq!(*lazy.or_init_with_arg("3").unwrap(), 3);
+    assert_eq!(calls.get(), 2);
+    assert_eq!(lazy.reset().as_deref(), Some(&3));
+    assert!(!lazy.is_initialized());
+}
+
+#[test]
+fn lazy_with_combines_families() {
+    let lazy: LazyWith<u32, str, Error, RcFamily, MutexFamily> = LazyWith::new(parse);
+    assert_eq!(*lazy.or_init_with_arg("5").unwrap(), 5);
+    let lazy: LazyWith<u32, str, Error, RcFamily, RefCellFamily> = LazyWith::new(parse);
+    assert_eq!(lazy.lazy().value(), None);
+}
@@ -0,0 +1,83 @@
+/*
+ * lazy_rc - Rc<T> and Arc<T> with *lazy* initialization
+ * This is free and unencumbered software released into the public domain.
+ */
+#![cfg(feature = "test-util")]
+
+use std::sync::LazyLock;
+use std::thread;
+
+use lazy_rc::{LazyArc, LazyRc, State};
+
+#[test]
+fn lazy_arc_override_scoped_restores_uninitialized() {
+    static CONFIG: LazyLock<LazyArc<u32>> = LazyLock::new(|| LazyArc::with_default_init(|| 1));
+    {
+        let _guard = CONFIG.override_scoped(2);
+        assert_eq!(*CONFIG.or_init(), 2);
+        assert_eq!(thread::spawn(|| *CONFIG.or_init()).join().unwrap(), 2);
+    }
+    assert!(!CONFIG.is_initialized());
+    assert_eq!(*CONFIG.or_init(), 1);
+}
+
+#[test]
+fn lazy_arc_override_scoped_nested() {
+    let lazy: LazyArc<u32> = LazyArc::from(1);
+    {
+        let _outer = lazy.override_scoped(2);
+        {
+            let _inner = lazy.override_scoped(3);
+            assert_eq!(*lazy.value().unwrap(), 3);
+        }
+        assert_eq!(*lazy.value().unwrap(), 2);
+    }
+    assert_eq!(*lazy.value().unwrap(), 1);
+}
+
+#[test]
+fn lazy_arc_override_scoped_local() {
+    static CONFIG: LazyLock<LazyArc<u32>> = LazyLock::new(|| LazyArc::with_default_init(|| 1));
+    {
+        let guard = CONFIG.override_scoped_local(2);
+        assert_eq!(*CONFIG.or_init(), 2);
+        assert_eq!(CONFIG.or_try_init().ok().as_deref(), Some(&2));
+        assert_eq!(thread::spawn(|| CONFIG.value().map(|value| *value)).join().unwrap(), None);
+        drop(guard);
+    }
+    assert_eq!(CONFIG.value(), None);
+    assert_eq!(*CONFIG.or_init(), 1);
+}
+
+#[test]
+fn lazy_rc_override_scoped() {
+    let lazy: LazyRc<String> = LazyRc::empty();
+    {
+        let _guard = lazy.override_scoped(String::from("mock"));
+        assert_eq!(lazy.value().as_deref().map(String::as_str), Some("mock"));
+    }
+    assert!(!lazy.is_initialized());
+    let generation = lazy.generation();
+    {
+        let _guard = lazy.override_scoped_local(String::from("local"));
+        assert_eq!(lazy.value().as_deref().map(String::as_str), Some("local"));
+    }
+    assert!(lazy.value().is_none());
+    assert!(lazy.generation() > generation);
+}
+
+#[test]
+fn lazy_arc_override_scoped_local_state() {
+    let lazy: LazyArc<u32> = LazyArc::empty();
+    assert_eq!(lazy.state(), State::Uninitialized);
+    {
+        let _guard = lazy.override_scoped_local(2);
+        assert!(lazy.is_initialized());
+        assert!(matches!(lazy.state(), State::Initialized { .. }));
+        thread::scope(|scope| {
+            scope.spawn(|| assert_eq!(lazy.state(), State::Uninitialized));
+        });
+    }
+    assert!(!lazy.is_initialized());
+    assert_eq!(lazy.state(), State::Uninitialized);
+}
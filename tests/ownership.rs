@@ -0,0 +1,68 @@
+/*
+ * lazy_rc - Rc<T> and Arc<T> with *lazy* initialization
+ * This is free and unencumbered software released into the public domain.
+ */
+use std::io::{Error, ErrorKind};
+use std::rc::Rc;
+use std::sync::Arc;
+
+use lazy_rc::{InitError, LazyArc, LazyRc};
+
+#[test]
+fn lazy_arc_into_inner() {
+    let lazy: LazyArc<String> = LazyArc::from(String::from("Hello"));
+    assert_eq!(lazy.into_inner().as_deref(), Some("Hello"));
+
+    let lazy: LazyArc<String> = LazyArc::from(String::from("Hello"));
+    let shared = lazy.value().unwrap();
+    assert_eq!(lazy.into_inner(), None);
+    assert_eq!(Arc::strong_count(&shared), 1);
+
+    let lazy: LazyArc<String> = LazyArc::empty();
+    assert_eq!(lazy.into_inner(), None);
+}
+
+#[test]
+fn lazy_arc_get_mut() {
+    let mut lazy: LazyArc<Vec<u32>> = LazyArc::empty();
+    assert!(lazy.get_mut().is_none());
+    lazy.or_init_with(|| vec![1]);
+    lazy.get_mut().unwrap().push(2);
+    let shared = lazy.value().unwrap();
+    assert!(lazy.get_mut().is_none());
+    drop(shared);
+    assert_eq!(lazy.get_mut().map(|value| value.len()), Some(2));
+}
+
+#[test]
+fn lazy_arc_make_mut_copies_shared_value() {
+    let mut lazy: LazyArc<Vec<u32>> = LazyArc::with_default_init(|| vec![1]);
+    lazy.make_mut().push(2);
+    let shared = lazy.value().unwrap();
+    lazy.make_mut().push(3);
+    assert_eq!(*shared, [1, 2]);
+    assert_eq!(*lazy.value().unwrap(), [1, 2, 3]);
+    assert!(!Arc::ptr_eq(&shared, &lazy.value().unwrap()));
+}
+
+#[test]
+fn lazy_arc_try_make_mut() {
+    let mut lazy: LazyArc<u32> = LazyArc::empty();
+    assert!(matches!(lazy.try_make_mut(), Err(InitError::NoDefaultInitializer)));
+    let mut lazy: LazyArc<u32> = LazyArc::with_failable_default_init(|| Err(Error::from(ErrorKind::NotFound)));
+    assert!(matches!(lazy.try_make_mut(), Err(InitError::Failed(error)) if error.kind() == ErrorKind::NotFound));
+    let mut lazy: LazyArc<u32> = LazyArc::with_failable_default_init(|| Ok(1));
+    *lazy.try_make_mut().unwrap() += 1;
+    assert_eq!(*lazy.value().unwrap(), 2);
+}
+
+#[test]
+fn lazy_rc_ownership() {
+    let mut lazy: LazyRc<String> = LazyRc::with_default_init(|| String::from("Hello"));
+    lazy.make_mut().push('!');
+    let shared = lazy.value().unwrap();
+    lazy.make_mut().push('?');
+    assert_eq!((shared.as_str(), Rc::strong_count(&shared)), ("Hello!", 1));
+    assert_eq!(lazy.get_mut().map(|value| value.as_str()), Some("Hello!?"));
+    assert_eq!(lazy.into_inner().as_deref(), Some("Hello!?"));
+}
@@ -0,0 +1,49 @@
+/*
+ * lazy_rc - Rc<T> and Arc<T> with *lazy* initialization
+ * This is free and unencumbered software released into the public domain.
+ */
+#![cfg(feature = "parking_lot")]
+
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, Barrier};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::thread;
+
+use lazy_rc::{ArcFamily, Lazy, LazyArc, ParkingLotFamily, State};
+
+#[test]
+fn lazy_arc_uses_parking_lot() {
+    let lazy: Lazy<u32, ArcFamily, ParkingLotFamily> = LazyArc::with_default_init(|| 1);
+    assert_eq!(*lazy.or_init(), 1);
+}
+
+#[test]
+fn lazy_arc_is_not_poisoned_by_panic() {
+    let lazy: LazyArc<u32> = LazyArc::empty();
+    let result = panic::catch_unwind(AssertUnwindSafe(|| lazy.or_init_with(|| panic!("Boom!"))));
+    assert!(result.is_err());
+    assert!(!lazy.is_initialized());
+    assert!(matches!(lazy.state(), State::Failed { .. }));
+    assert_eq!(*lazy.or_init_with(|| 2), 2);
+}
+
+#[test]
+fn lazy_arc_initializes_once_under_contention() {
+    let calls = Arc::new(AtomicU32::new(0));
+    let lazy: Arc<LazyArc<u32>> = {
+        let calls = Arc::clone(&calls);
+        Arc::new(LazyArc::with_default_init(move || calls.fetch_add(1, Ordering::SeqCst) + 1))
+    };
+    let barrier = Arc::new(Barrier::new(8));
+    let threads: Vec<_> = (0..8).map(|_| {
+        let (lazy, barrier) = (Arc::clone(&lazy), Arc::clone(&barrier));
+        thread::spawn(move || {
+            barrier.wait();
+            *lazy.or_init()
+        })
+    }).collect();
+    for thread in threads {
+        assert_eq!(thread.join().unwrap(), 1);
+    }
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
@@ -0,0 +1,107 @@
+/*
+ * lazy_rc - Rc<T> and Arc<T> with *lazy* initialization
+ * This is free and unencumbered software released into the public domain.
+ */
+#![cfg(feature = "shutdown")]
+
+use std::any::type_name;
+use std::sync::{self, Mutex, MutexGuard};
+use std::thread;
+use std::time::Duration;
+
+use lazy_rc::{shutdown, LazyArc, ShutdownEntry, ShutdownOutcome};
+
+/// The registry is global, so the tests must not run concurrently.
+static SERIAL: Mutex<()> = Mutex::new(());
+
+struct First;
+struct Second;
+struct Held;
+struct Cyclic {
+    _this: sync::Weak<Cyclic>,
+}
+struct SlowDrop;
+struct PanickingDrop;
+
+impl Drop for SlowDrop {
+    fn drop(&mut self) {
+        thread::sleep(Duration::from_millis(500));
+    }
+}
+
+impl Drop for PanickingDrop {
+    fn drop(&mut self) {
+        panic!("Drop has failed!");
+    }
+}
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+fn serial() -> MutexGuard<'static, ()> {
+    SERIAL.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn entry<T>(outcome: ShutdownOutcome) -> ShutdownEntry {
+    ShutdownEntry { type_name: type_name::<T>(), outcome }
+}
+
+#[test]
+fn shutdown_in_reverse_init_order() {
+    static FIRST: LazyArc<First> = LazyArc::empty();
+    static SECOND: LazyArc<Second> = LazyArc::empty();
+    let _serial = serial();
+    SECOND.register_shutdown(TIMEOUT);
+    FIRST.register_shutdown(TIMEOUT);
+    FIRST.or_init_with(|| First);
+    SECOND.or_init_with(|| Second);
+    let report = shutdown();
+    assert_eq!(report.entries, vec![entry::<Second>(ShutdownOutcome::Dropped), entry::<First>(ShutdownOutcome::Dropped)]);
+    assert!(report.is_clean());
+    assert!(!FIRST.is_initialized() && !SECOND.is_initialized());
+    assert!(shutdown().entries.is_empty());
+}
+
+#[test]
+fn shutdown_reports_still_referenced() {
+    static HELD: LazyArc<Held> = LazyArc::empty();
+    let _serial = serial();
+    HELD.register_shutdown(TIMEOUT);
+    let value = HELD.or_init_with(|| Held);
+    let report = shutdown();
+    assert_eq!(report.entries, vec![entry::<Held>(ShutdownOutcome::StillReferenced)]);
+    assert!(!report.is_clean());
+    assert_eq!(report.still_referenced().count(), 1);
+    drop(value);
+}
+
+#[test]
+fn shutdown_ignores_weak_pointers() {
+    static CYCLIC: LazyArc<Cyclic> = LazyArc::empty();
+    let _serial = serial();
+    CYCLIC.register_shutdown(TIMEOUT);
+    CYCLIC.or_init_cyclic(|weak| Cyclic { _this: weak.clone() });
+    assert_eq!(shutdown().entries, vec![entry::<Cyclic>(ShutdownOutcome::Dropped)]);
+}
+
+#[test]
+fn shutdown_respects_per_instance_timeout() {
+    static SLOW: LazyArc<SlowDrop> = LazyArc::empty();
+    let _serial = serial();
+    SLOW.register_shutdown(Duration::from_millis(50));
+    SLOW.or_init_with(|| SlowDrop);
+    assert_eq!(shutdown().entries, vec![entry::<SlowDrop>(ShutdownOutcome::TimedOut)]);
+
+    SLOW.register_shutdown(TIMEOUT);
+    SLOW.or_init_with(|| SlowDrop);
+    assert_eq!(shutdown().entries, vec![entry::<SlowDrop>(ShutdownOutcome::Dropped)]);
+}
+
+#[test]
+fn shutdown_reports_panicking_drop() {
+    static PANICKING: LazyArc<PanickingDrop> = LazyArc::empty();
+    let _serial = serial();
+    PANICKING.register_shutdown(TIMEOUT);
+    PANICKING.or_init_with(|| PanickingDrop);
+    assert_eq!(shutdown().entries, vec![entry::<PanickingDrop>(ShutdownOutcome::Panicked)]);
+    assert!(!PANICKING.is_initialized());
+}
@@ -0,0 +1,97 @@
+/*
+ * lazy_rc - Rc<T> and Arc<T> with *lazy* initialization
+ * This is free and unencumbered software released into the public domain.
+ */
+use std::env;
+use std::fs;
+use std::num::ParseIntError;
+use std::path::PathBuf;
+
+use lazy_rc::{InitError, LazyArc};
+
+fn temp_file(name: &str, content: &str) -> PathBuf {
+    let path = env::temp_dir().join(format!("lazy_rc-sources-{}-{}", std::process::id(), name));
+    fs::write(&path, content).unwrap();
+    path
+}
+
+#[test]
+fn from_env_parses_variable() {
+    env::set_var("LAZY_RC_TEST_SOURCES_VALID", "42");
+    let lazy: LazyArc<u32> = LazyArc::from_env("LAZY_RC_TEST_SOURCES_VALID", str::parse::<u32>);
+    assert_eq!(*lazy.or_try_init().unwrap(), 42);
+}
+
+#[test]
+fn from_env_error_names_variable() {
+    env::remove_var("LAZY_RC_TEST_SOURCES_MISSING");
+    let lazy: LazyArc<u32> = LazyArc::from_env("LAZY_RC_TEST_SOURCES_MISSING", str::parse::<u32>);
+    match lazy.or_try_init() {
+        Err(InitError::Env { name, .. }) => assert_eq!(name, "LAZY_RC_TEST_SOURCES_MISSING"),
+        other => panic!("Unexpected result: {:?}", other),
+    }
+
+    env::set_var("LAZY_RC_TEST_SOURCES_INVALID", "forty-two");
+    let lazy: LazyArc<u32> = LazyArc::from_env("LAZY_RC_TEST_SOURCES_INVALID", str::parse::<u32>);
+    let error = lazy.or_try_init().unwrap_err();
+    assert!(error.to_string().contains("LAZY_RC_TEST_SOURCES_INVALID"));
+    match error {
+        InitError::Env { error, .. } => assert!(error.is::<ParseIntError>()),
+        other => panic!("Unexpected error: {:?}", other),
+    }
+    assert!(!lazy.is_initialized());
+}
+
+#[test]
+fn from_file_error_names_path() {
+    let path = temp_file("invalid.txt", "forty-two");
+    let lazy: LazyArc<u32> = LazyArc::from_file(path.clone(), |text: &str| text.trim().parse::<u32>());
+    let error = lazy.or_try_init().unwrap_err();
+    assert!(error.to_string().contains(&*path.to_string_lossy()));
+    match error {
+        InitError::File { path: error_path, error } => {
+            assert_eq!(error_path, path);
+            assert!(error.is::<ParseIntError>());
+        },
+        other => panic!("Unexpected error: {:?}", other),
+    }
+    fs::remove_file(&path).unwrap();
+
+    let lazy: LazyArc<u32> = LazyArc::from_file(path.clone(), |text: &str| text.trim().parse::<u32>());
+    match lazy.or_try_init() {
+        Err(InitError::File { path: error_path, .. }) => assert_eq!(error_path, path),
+        other => panic!("Unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn from_file_parses_content() {
+    let path = temp_file("valid.txt", "42\n");
+    let lazy: LazyArc<u32> = LazyArc::from_file(path.clone(), |text: &str| text.trim().parse::<u32>());
+    assert_eq!(*lazy.or_try_init().unwrap(), 42);
+    fs::remove_file(&path).unwrap();
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn from_json_file_error_names_path() {
+    let path = temp_file("invalid.json", "{ not json");
+    let lazy: LazyArc<Vec<u32>> = LazyArc::from_json_file(path.clone());
+    match lazy.or_try_init() {
+        Err(InitError::File { path: error_path, .. }) => assert_eq!(error_path, path),
+        other => panic!("Unexpected result: {:?}", other),
+    }
+    fs::remove_file(&path).unwrap();
+}
+
+#[cfg(feature = "toml")]
+#[test]
+fn from_toml_file_error_names_path() {
+    let path = temp_file("invalid.toml", "= not toml");
+    let lazy: LazyArc<std::collections::BTreeMap<String, u32>> = LazyArc::from_toml_file(path.clone());
+    match lazy.or_try_init() {
+        Err(InitError::File { path: error_path, .. }) => assert_eq!(error_path, path),
+        other => panic!("Unexpected result: {:?}", other),
+    }
+    fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,84 @@
+/*
+ * lazy_rc - Rc<T> and Arc<T> with *lazy* initialization
+ * This is free and unencumbered software released into the public domain.
+ */
+use std::io::{Error, ErrorKind};
+use std::panic::{AssertUnwindSafe, catch_unwind};
+use std::sync::{Arc, Barrier};
+use std::thread;
+
+use lazy_rc::{LazyArc, LazyRc, State};
+
+#[allow(dead_code)] // Neither displayable nor debuggable
+struct OpaqueError {
+    code: u32,
+}
+
+#[test]
+fn lazy_arc_state_transitions() {
+    let lazy: Arc<LazyArc<u32>> = Arc::new(LazyArc::empty());
+    assert_eq!(lazy.state(), State::Uninitialized);
+
+    let (started, finish) = (Arc::new(Barrier::new(2)), Arc::new(Barrier::new(2)));
+    let initializer = {
+        let (lazy, started, finish) = (Arc::clone(&lazy), Arc::clone(&started), Arc::clone(&finish));
+        thread::spawn(move || {
+            let thread = thread::current().id();
+            lazy.or_init_with(|| {
+                started.wait();
+                finish.wait();
+                42
+            });
+            thread
+        })
+    };
+    started.wait();
+    let state = lazy.state();
+    finish.wait();
+    let thread = initializer.join().unwrap();
+    assert!(matches!(state, State::Initializing { thread: initializing, .. } if initializing == thread));
+    assert!(matches!(lazy.state(), State::Initialized { .. }));
+
+    lazy.reset();
+    assert_eq!(lazy.state(), State::Uninitialized);
+}
+
+#[test]
+fn lazy_arc_state_failed() {
+    let lazy: LazyArc<u32> = LazyArc::empty();
+    assert!(lazy.or_try_init_with(|| Err(OpaqueError { code: 7 })).is_err());
+    match lazy.state() {
+        State::Failed { error } => assert!(error.contains("OpaqueError"), "{}", error),
+        state => panic!("Unexpected state: {:?}", state),
+    }
+    assert!(matches!(lazy.or_try_init_with(|| Ok::<_, OpaqueError>(42)), Ok(value) if *value == 42));
+    assert!(matches!(lazy.state(), State::Initialized { .. }));
+}
+
+#[test]
+fn lazy_arc_state_failed_default_init() {
+    let lazy: LazyArc<u32> = LazyArc::with_failable_default_init(|| Err(Error::new(ErrorKind::NotFound, "missing config")));
+    assert!(lazy.or_try_init().is_err());
+    assert_eq!(lazy.state(), State::Failed { error: String::from("missing config") });
+}
+
+#[test]
+fn lazy_rc_state_panicked() {
+    let lazy: LazyRc<u32> = LazyRc::empty();
+    assert!(catch_unwind(AssertUnwindSafe(|| lazy.or_init_with(|| panic!("Initializer failed!")))).is_err());
+    assert!(matches!(lazy.state(), State::Failed { error } if error.contains("panicked")));
+    lazy.or_init_with(|| 42);
+    assert!(matches!(lazy.state(), State::Initialized { .. }));
+}
+
+#[test]
+fn lazy_rc_state_transitions() {
+    let lazy: LazyRc<u32> = LazyRc::empty();
+    assert_eq!(lazy.state(), State::Uninitialized);
+    assert!(lazy.or_try_init_with(|| Err(OpaqueError { code: 3 })).is_err());
+    assert!(matches!(lazy.state(), State::Failed { error } if error.contains("OpaqueError")));
+    lazy.or_init_with(|| 42);
+    assert!(matches!(lazy.state(), State::Initialized { .. }));
+    lazy.reset();
+    assert_eq!(lazy.state(), State::Uninitialized);
+}
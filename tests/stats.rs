@@ -0,0 +1,52 @@
+/*
+ * lazy_rc - Rc<T> and Arc<T> with *lazy* initialization
+ * This is free and unencumbered software released into the public domain.
+ */
+#![cfg(feature = "stats")]
+
+use std::io::{Error, ErrorKind};
+use std::thread;
+use std::time::Duration;
+
+use lazy_rc::{LazyArc, LazyRc, Stats};
+
+#[test]
+fn lazy_arc_stats_count_outcomes() {
+    let lazy: LazyArc<u32> = LazyArc::empty();
+    assert_eq!(lazy.stats(), Stats::default());
+    assert!(lazy.or_try_init_with(|| Err(Error::from(ErrorKind::NotFound))).is_err());
+    lazy.or_init_with(|| {
+        thread::sleep(Duration::from_millis(10));
+        1
+    });
+    lazy.or_init_with(|| 2);
+    lazy.reset();
+
+    let stats = lazy.stats();
+    assert_eq!((stats.accesses, stats.initializations, stats.failures, stats.resets), (3, 1, 1, 1));
+    assert!(stats.max_init_time >= Duration::from_millis(10));
+    assert!(stats.init_time >= stats.max_init_time);
+}
+
+#[test]
+fn lazy_arc_stats_count_default_init() {
+    let lazy: LazyArc<u32> = LazyArc::with_failable_default_init(|| Err(Error::from(ErrorKind::NotFound)));
+    assert!(lazy.or_try_init().is_err());
+    assert!(lazy.or_try_init().is_err());
+    lazy.replace(1);
+    assert!(lazy.or_try_init().is_ok());
+
+    let stats = lazy.stats();
+    assert_eq!((stats.accesses, stats.initializations, stats.failures), (3, 0, 2));
+}
+
+#[test]
+fn lazy_rc_stats() {
+    let lazy: LazyRc<u32> = LazyRc::with_default_init(|| 1);
+    lazy.or_init();
+    lazy.or_init();
+    assert_eq!(lazy.value().as_deref(), Some(&1));
+
+    let stats = lazy.stats();
+    assert_eq!((stats.accesses, stats.initializations, stats.failures, stats.resets), (3, 1, 0, 0));
+}
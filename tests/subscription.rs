@@ -0,0 +1,79 @@
+/*
+ * lazy_rc - Rc<T> and Arc<T> with *lazy* initialization
+ * This is free and unencumbered software released into the public domain.
+ */
+use std::sync::{Arc, Barrier};
+use std::sync::mpsc::{RecvTimeoutError, TryRecvError};
+use std::thread;
+use std::time::Duration;
+
+use lazy_rc::{LazyArc, LazyRc};
+
+#[test]
+fn lazy_arc_subscription_receives_latest_change() {
+    let lazy: LazyArc<u32> = LazyArc::empty();
+    let subscription = lazy.subscribe();
+    assert!(subscription.initial().is_none());
+    assert_eq!(subscription.try_recv(), Err(TryRecvError::Empty));
+
+    lazy.or_init_with(|| 1);
+    lazy.or_init_with(|| 2);
+    assert_eq!(subscription.recv().unwrap().as_deref(), Some(&1));
+    assert_eq!(subscription.try_recv(), Err(TryRecvError::Empty));
+    lazy.replace(3);
+    lazy.replace(4);
+    assert_eq!(subscription.recv().unwrap().as_deref(), Some(&4));
+    lazy.replace(5);
+    lazy.reset();
+    assert_eq!(subscription.recv().unwrap(), None);
+    assert_eq!(subscription.recv_timeout(Duration::from_millis(10)), Err(RecvTimeoutError::Timeout));
+}
+
+#[test]
+fn lazy_arc_subscription_disconnects_on_drop() {
+    let lazy: LazyArc<u32> = LazyArc::from(1);
+    let subscription = lazy.subscribe();
+    assert_eq!(subscription.initial().map(|value| **value), Some(1));
+    lazy.replace(2);
+    assert_eq!(subscription.recv_timeout(Duration::MAX).unwrap().as_deref(), Some(&2));
+    lazy.replace(3);
+    drop(lazy);
+    assert_eq!(subscription.try_recv().unwrap().as_deref(), Some(&3));
+    assert_eq!(subscription.try_recv(), Err(TryRecvError::Disconnected));
+    assert!(subscription.recv().is_err());
+}
+
+#[test]
+fn lazy_arc_subscription_misses_no_latest_update() {
+    const UPDATES: u32 = 1000;
+    let lazy: Arc<LazyArc<u32>> = Arc::new(LazyArc::from(0));
+    let barrier = Arc::new(Barrier::new(2));
+    let writer = {
+        let (lazy, barrier) = (Arc::clone(&lazy), Arc::clone(&barrier));
+        thread::spawn(move || {
+            barrier.wait();
+            for value in 1..=UPDATES {
+                lazy.replace(value);
+            }
+        })
+    };
+    barrier.wait();
+    let subscription = lazy.subscribe();
+    let mut latest = **subscription.initial().unwrap();
+    while latest < UPDATES {
+        let value = *subscription.recv().unwrap().unwrap();
+        assert!(value > latest, "{} after {}", value, latest);
+        latest = value;
+    }
+    writer.join().unwrap();
+}
+
+#[test]
+fn lazy_rc_subscription() {
+    let lazy: LazyRc<String> = LazyRc::empty();
+    let subscription = lazy.subscribe();
+    lazy.or_init_with(|| String::from("a"));
+    lazy.replace(String::from("b"));
+    assert_eq!(subscription.try_recv().unwrap().as_deref().map(String::as_str), Some("b"));
+    assert_eq!(subscription.try_recv(), Err(TryRecvError::Empty));
+}
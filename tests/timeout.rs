@@ -0,0 +1,92 @@
+/*
+ * lazy_rc - Rc<T> and Arc<T> with *lazy* initialization
+ * This is free and unencumbered software released into the public domain.
+ */
+use std::io::ErrorKind;
+use std::sync::{Arc, Barrier};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use lazy_rc::{CancellationToken, InitError, LazyArc, State};
+
+#[test]
+fn lazy_arc_timeout_without_default_init() {
+    let lazy: LazyArc<u32> = LazyArc::empty();
+    assert!(matches!(lazy.or_try_init_timeout(Duration::from_secs(1)), Err(InitError::NoDefaultInitializer)));
+}
+
+#[test]
+fn lazy_arc_timeout_while_waiting() {
+    let lazy: Arc<LazyArc<u32>> = Arc::new(LazyArc::with_default_init(|| 2));
+    let barrier = Arc::new(Barrier::new(2));
+    let slow = {
+        let (lazy, barrier) = (Arc::clone(&lazy), Arc::clone(&barrier));
+        thread::spawn(move || *lazy.or_init_with(|| {
+            barrier.wait();
+            thread::sleep(Duration::from_millis(200));
+            1
+        }))
+    };
+    barrier.wait();
+    let start = Instant::now();
+    assert!(matches!(lazy.or_try_init_timeout(Duration::from_millis(20)), Err(InitError::Timeout)));
+    assert!(start.elapsed() < Duration::from_millis(200));
+    assert!(matches!(lazy.state(), State::Initializing { .. }));
+
+    // The pending initialization is not affected by the timeout
+    assert_eq!(slow.join().unwrap(), 1);
+    assert_eq!(*lazy.or_try_init_timeout(Duration::ZERO).unwrap(), 1);
+}
+
+#[test]
+fn lazy_arc_timeout_waits_for_pending_initialization() {
+    let lazy: Arc<LazyArc<u32>> = Arc::new(LazyArc::with_default_init(|| 2));
+    let barrier = Arc::new(Barrier::new(2));
+    let slow = {
+        let (lazy, barrier) = (Arc::clone(&lazy), Arc::clone(&barrier));
+        thread::spawn(move || *lazy.or_init_with(|| {
+            barrier.wait();
+            thread::sleep(Duration::from_millis(50));
+            1
+        }))
+    };
+    barrier.wait();
+    assert_eq!(*lazy.or_try_init_timeout(Duration::from_secs(60)).unwrap(), 1);
+    assert_eq!(slow.join().unwrap(), 1);
+}
+
+#[test]
+fn lazy_arc_timeout_without_deadline() {
+    let lazy: LazyArc<u32> = LazyArc::with_cancellable_default_init(|token: &CancellationToken| {
+        assert!(token.deadline().is_none() && !token.is_cancelled());
+        Ok(1)
+    });
+    assert_eq!(*lazy.or_try_init_timeout(Duration::MAX).unwrap(), 1);
+}
+
+#[test]
+fn lazy_arc_cancellable_default_init() {
+    let lazy: LazyArc<u32> = LazyArc::with_cancellable_default_init(|token: &CancellationToken| {
+        while token.remaining().is_some_and(|remaining| !remaining.is_zero()) {
+            thread::sleep(Duration::from_millis(1));
+        }
+        token.check()?;
+        Ok(1)
+    });
+    let start = Instant::now();
+    assert!(matches!(lazy.or_try_init_timeout(Duration::from_millis(20)), Err(InitError::Timeout)));
+    assert!(start.elapsed() >= Duration::from_millis(20));
+    assert!(!lazy.is_initialized());
+
+    // Other initialization functions pass a token that is never cancelled
+    assert_eq!(*lazy.or_try_init().unwrap(), 1);
+}
+
+#[test]
+fn lazy_arc_cancellable_default_init_error() {
+    let lazy: LazyArc<u32> = LazyArc::with_cancellable_default_init(|token: &CancellationToken| {
+        assert!(token.deadline().is_none() && !token.is_cancelled());
+        Err(ErrorKind::NotFound.into())
+    });
+    assert!(matches!(lazy.or_try_init(), Err(InitError::Failed(error)) if error.kind() == ErrorKind::NotFound));
+}
@@ -0,0 +1,50 @@
+/*
+ * lazy_rc - Rc<T> and Arc<T> with *lazy* initialization
+ * This is free and unencumbered software released into the public domain.
+ */
+use std::fmt::Display;
+use std::io::{Error, ErrorKind};
+use std::rc::Rc;
+use std::sync::Arc;
+
+use lazy_rc::{InitError, LazyArc, LazyRc};
+
+#[test]
+fn lazy_arc_str() {
+    let lazy: LazyArc<str> = LazyArc::with_default_init_arc(|| "Hello");
+    assert_eq!(&*lazy.or_init(), "Hello");
+    assert_eq!(&*lazy.replace(Arc::<str>::from("World")).unwrap(), "Hello");
+    assert_eq!(&*lazy.unwrap(), "World");
+}
+
+#[test]
+fn lazy_arc_slice() {
+    let lazy: LazyArc<[u32]> = LazyArc::empty();
+    assert_eq!(&*lazy.or_init_with_arc(|| vec![1, 2, 3]), &[1, 2, 3]);
+    assert_eq!(&*lazy.or_init_with_arc(|| vec![4, 5]), &[1, 2, 3]);
+}
+
+#[test]
+fn lazy_arc_dyn_trait() {
+    let lazy: LazyArc<dyn Display + Send + Sync> = LazyArc::with_default_init_pointer(|| Arc::new(42u32) as Arc<dyn Display + Send + Sync>);
+    assert_eq!(lazy.or_init().to_string(), "42");
+    let lazy: LazyArc<dyn Display> = LazyArc::from(Box::new(1.5f64) as Box<dyn Display>);
+    assert_eq!(lazy.value().unwrap().to_string(), "1.5");
+}
+
+#[test]
+fn lazy_arc_failable_unsized() {
+    let lazy: LazyArc<str> = LazyArc::with_failable_default_init_arc(|| Err::<String, _>(Error::from(ErrorKind::NotFound)));
+    assert!(matches!(lazy.or_try_init(), Err(InitError::Failed(error)) if error.kind() == ErrorKind::NotFound));
+    let error = lazy.or_try_init_with_arc(|| Err::<String, _>(Error::from(ErrorKind::InvalidData))).err().unwrap();
+    assert_eq!(error.kind(), ErrorKind::InvalidData);
+    assert!(!lazy.is_initialized());
+}
+
+#[test]
+fn lazy_rc_unsized() {
+    let lazy: LazyRc<str> = LazyRc::empty();
+    assert_eq!(&*lazy.or_init_with_rc(|| String::from("Hello")), "Hello");
+    let lazy: LazyRc<[u8]> = LazyRc::from(Rc::<[u8]>::from(&b"abc"[..]));
+    assert_eq!(&*lazy.value().unwrap(), b"abc");
+}
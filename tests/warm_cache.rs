@@ -0,0 +1,72 @@
+/*
+ * lazy_rc - Rc<T> and Arc<T> with *lazy* initialization
+ * This is free and unencumbered software released into the public domain.
+ */
+#![cfg(feature = "persist")]
+
+use std::convert::Infallible;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::thread;
+
+use lazy_rc::WarmCache;
+
+fn cache_path(name: &str) -> PathBuf {
+    let path = env::temp_dir().join(format!("lazy_rc-warm-cache-{}-{}", std::process::id(), name));
+    let _ = fs::remove_file(&path);
+    path
+}
+
+#[test]
+fn warm_cache_round_trip() {
+    let cache = WarmCache::new(cache_path("round-trip.cache"), "numbers", 1);
+    assert_eq!(cache.load::<Vec<u32>>(), None);
+    assert_eq!(cache.load_or_init(|| Ok::<_, Infallible>(vec![1u32, 2, 3])).unwrap(), vec![1, 2, 3]);
+    assert_eq!(cache.load_or_init(|| Err::<Vec<u32>, _>("Value should be cached!")).unwrap(), vec![1, 2, 3]);
+    fs::remove_file(cache.path()).unwrap();
+}
+
+#[test]
+fn warm_cache_ignores_wrong_version_or_key() {
+    let path = cache_path("version.cache");
+    WarmCache::new(path.clone(), "numbers", 1).store(&vec![1u32]).unwrap();
+    assert_eq!(WarmCache::new(path.clone(), "numbers", 2).load::<Vec<u32>>(), None);
+    assert_eq!(WarmCache::new(path.clone(), "other", 1).load::<Vec<u32>>(), None);
+    let cache = WarmCache::new(path.clone(), "numbers", 2);
+    assert_eq!(cache.load_or_init(|| Ok::<_, Infallible>(vec![2u32])).unwrap(), vec![2]);
+    assert_eq!(cache.load::<Vec<u32>>(), Some(vec![2]));
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn warm_cache_recomputes_corrupt_file() {
+    let cache = WarmCache::new(cache_path("corrupt.cache"), "numbers", 1);
+    cache.store(&vec![1u32, 2, 3]).unwrap();
+    let mut data = fs::read(cache.path()).unwrap();
+    *data.last_mut().unwrap() ^= 0x01;
+    fs::write(cache.path(), &data).unwrap();
+    assert_eq!(cache.load::<Vec<u32>>(), None);
+    assert_eq!(cache.load_or_init(|| Ok::<_, Infallible>(vec![4u32])).unwrap(), vec![4]);
+
+    fs::write(cache.path(), "garbage").unwrap();
+    assert_eq!(cache.load_or_init(|| Ok::<_, Infallible>(vec![5u32])).unwrap(), vec![5]);
+    assert_eq!(cache.load::<Vec<u32>>(), Some(vec![5]));
+    fs::remove_file(cache.path()).unwrap();
+}
+
+#[test]
+fn warm_cache_concurrent_stores() {
+    let cache = Arc::new(WarmCache::new(cache_path("concurrent.cache"), "numbers", 1));
+    let threads: Vec<_> = (0..8u32).map(|index| {
+        let cache = Arc::clone(&cache);
+        thread::spawn(move || (0..16).all(|_| cache.store(&vec![index; 1024]).is_ok()))
+    }).collect();
+    for thread in threads {
+        assert!(thread.join().unwrap());
+    }
+    let value = cache.load::<Vec<u32>>().unwrap();
+    assert!(value.len() == 1024 && value.iter().all(|item| *item == value[0]));
+    fs::remove_file(cache.path()).unwrap();
+}